@@ -73,6 +73,7 @@ pub enum Tile {
     Space = 63,
     GreySolid = 64,
     GreyWhite = 65,
+    Ghost = 66,
     GreenSolid = 80,
     GreenWhite = 81,
     CyanSolid = 96,
@@ -81,6 +82,14 @@ pub enum Tile {
     BlueWhite = 113,
     VioletSolid = 128,
     VioletWhite = 129,
+    RedSolid = 144,
+    RedWhite = 145,
+    OrangeSolid = 160,
+    OrangeWhite = 161,
+    YellowSolid = 176,
+    YellowWhite = 177,
+    PurpleSolid = 192,
+    PurpleWhite = 193,
 }
 
 #[derive(PartialEq, Debug)]
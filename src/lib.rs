@@ -1,6 +1,5 @@
 #![deny(bare_trait_objects)]
 extern crate cfg_if;
-extern crate rand;
 extern crate wasm_bindgen;
 
 mod grid;
@@ -10,7 +9,7 @@ mod tetrust;
 use cfg_if::cfg_if;
 use grid::{Grid, Tile};
 use linear::M4;
-use tetrust::Regulator;
+use tetrust::{from_base64, to_base64, Playing, Regulator};
 use wasm_bindgen::prelude::*;
 
 cfg_if! {
@@ -25,6 +24,8 @@ cfg_if! {
 
 #[wasm_bindgen]
 extern "C" {
+    #[wasm_bindgen(js_namespace = Date)]
+    fn now() -> f64;
     pub fn alert(s: &str);
     #[wasm_bindgen(js_namespace = console)]
     pub fn log(s: &str);
@@ -50,13 +51,14 @@ const GRID_HEIGHT: u32 = 27;
 #[wasm_bindgen]
 pub fn render(t: f32, wd: u32, ht: u32) -> Scene {
     let regulator: &mut Regulator = Regulator::get(t);
+    regulator.date = (now() / 86_400_000.0) as u32;
     *regulator = regulator.iterate(t);
 
     let clip_matrix: &mut M4 = get_clip_matrix();
     let clip_matrix_n = calc_clip_matrix(wd, ht);
 
     let grid: &mut Grid = get_grid();
-    let grid_n = regulator.game.grid();
+    let grid_n = regulator.grid();
 
     let clip_matrix_changed = *clip_matrix != clip_matrix_n;
     *clip_matrix = clip_matrix_n;
@@ -75,12 +77,81 @@ pub fn render(t: f32, wd: u32, ht: u32) -> Scene {
 
 #[wasm_bindgen]
 pub fn on_key_down(t: f32, key_code: u32, _key: &str) {
-    Regulator::get(t).controls.key_down(key_code);
+    Regulator::get(t).key_down(key_code);
 }
 
 #[wasm_bindgen]
 pub fn on_key_up(t: f32, key_code: u32, _key: &str) {
-    Regulator::get(t).controls.key_up(key_code);
+    Regulator::get(t).key_up(key_code);
+}
+
+#[wasm_bindgen]
+pub fn on_blur(t: f32) {
+    Regulator::get(t).blur();
+}
+
+#[wasm_bindgen]
+pub fn set_seed(t: f32, seed: Option<u32>) {
+    let regulator: &mut Regulator = Regulator::get(t);
+    regulator.game = regulator.game.with_seed(seed);
+}
+
+// snapshot of the game in progress, None when there is nothing to resume
+#[wasm_bindgen]
+pub fn save_state(t: f32) -> Option<Vec<u8>> {
+    Regulator::get(t).game.saved().map(|playing| playing.save())
+}
+
+// offers a snapshot from save_state as Continue in the main menu
+#[wasm_bindgen]
+pub fn load_state(t: f32, bytes: &[u8]) -> Result<(), JsValue> {
+    let playing = Playing::load(bytes).map_err(|error| JsValue::from_str(&error.to_string()))?;
+    let regulator: &mut Regulator = Regulator::get(t);
+
+    regulator.game = regulator.game.with_saved(playing);
+    Ok(())
+}
+
+// name recorded with new high scores
+#[wasm_bindgen]
+pub fn set_name(t: f32, name: &str) {
+    Regulator::get(t).name = name.to_string();
+}
+
+// names the entry of a game that placed on the high score board, reported by
+// the new_high_score event
+#[wasm_bindgen]
+pub fn rename_high_score(t: f32, name: &str) {
+    Regulator::get(t).rename(name);
+}
+
+// tile atlas image to draw the grid with, it changes with the theme
+#[wasm_bindgen]
+pub fn atlas(t: f32) -> String {
+    Regulator::get(t).atlas().to_string()
+}
+
+// shareable code for the last finished game
+#[wasm_bindgen]
+pub fn replay_code(t: f32) -> Option<String> {
+    Regulator::get(t).replay.as_ref().map(to_base64)
+}
+
+#[wasm_bindgen]
+pub fn watch_replay(t: f32, code: &str) -> Result<(), JsValue> {
+    let recording = from_base64(code).map_err(|error| JsValue::from_str(&error.to_string()))?;
+
+    Regulator::get(t).watch(&recording);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn take_events(t: f32) -> Vec<String> {
+    Regulator::get(t)
+        .events
+        .drain(..)
+        .map(|event| event.to_string())
+        .collect()
 }
 
 #[wasm_bindgen]
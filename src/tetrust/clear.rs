@@ -0,0 +1,112 @@
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spin {
+    None,
+    Mini,
+    Full,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clear {
+    None,
+    Single,
+    Double,
+    Triple,
+    Tetris,
+    TSpinMini,
+    TSpinMiniSingle,
+    TSpinMiniDouble,
+    TSpin,
+    TSpinSingle,
+    TSpinDouble,
+    TSpinTriple,
+}
+
+impl Clear {
+    pub fn new(spin: Spin, rows: u32) -> Self {
+        match (spin, rows) {
+            (Spin::None, 0) => Clear::None,
+            (Spin::None, 1) => Clear::Single,
+            (Spin::None, 2) => Clear::Double,
+            (Spin::None, 3) => Clear::Triple,
+            (Spin::None, _) => Clear::Tetris,
+            (Spin::Mini, 0) => Clear::TSpinMini,
+            (Spin::Mini, 1) => Clear::TSpinMiniSingle,
+            (Spin::Mini, _) => Clear::TSpinMiniDouble,
+            (Spin::Full, 0) => Clear::TSpin,
+            (Spin::Full, 1) => Clear::TSpinSingle,
+            (Spin::Full, 2) => Clear::TSpinDouble,
+            (Spin::Full, _) => Clear::TSpinTriple,
+        }
+    }
+
+    // tetrises and line clearing t-spins keep a back to back chain going
+    pub fn difficult(self) -> bool {
+        matches!(
+            self,
+            Clear::Tetris
+                | Clear::TSpinMiniSingle
+                | Clear::TSpinMiniDouble
+                | Clear::TSpinSingle
+                | Clear::TSpinDouble
+                | Clear::TSpinTriple
+        )
+    }
+
+    // guideline points awarded per level
+    pub fn points(self) -> u32 {
+        match self {
+            Clear::None => 0,
+            Clear::Single => 100,
+            Clear::Double => 300,
+            Clear::Triple => 500,
+            Clear::Tetris => 800,
+            Clear::TSpinMini => 100,
+            Clear::TSpinMiniSingle => 200,
+            Clear::TSpinMiniDouble => 400,
+            Clear::TSpin => 400,
+            Clear::TSpinSingle => 800,
+            Clear::TSpinDouble => 1200,
+            Clear::TSpinTriple => 1600,
+        }
+    }
+
+    // guideline garbage lines sent before bonuses
+    pub fn attack(self) -> u32 {
+        match self {
+            Clear::None | Clear::Single => 0,
+            Clear::Double => 1,
+            Clear::Triple => 2,
+            Clear::Tetris => 4,
+            Clear::TSpinMini | Clear::TSpinMiniSingle | Clear::TSpin => 0,
+            Clear::TSpinMiniDouble => 1,
+            Clear::TSpinSingle => 2,
+            Clear::TSpinDouble => 4,
+            Clear::TSpinTriple => 6,
+        }
+    }
+}
+
+impl Display for Clear {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Clear::None => "",
+                Clear::Single => "SINGLE",
+                Clear::Double => "DOUBLE",
+                Clear::Triple => "TRIPLE",
+                Clear::Tetris => "TETRIS",
+                Clear::TSpinMini => "MINI T-SPIN",
+                Clear::TSpinMiniSingle => "MINI T-SPIN SINGLE",
+                Clear::TSpinMiniDouble => "MINI T-SPIN DOUBLE",
+                Clear::TSpin => "T-SPIN",
+                Clear::TSpinSingle => "T-SPIN SINGLE",
+                Clear::TSpinDouble => "T-SPIN DOUBLE",
+                Clear::TSpinTriple => "T-SPIN TRIPLE",
+            }
+        )
+    }
+}
@@ -0,0 +1,372 @@
+use std::fmt::{Display, Formatter, Result};
+use std::ops::RangeInclusive;
+use tetrust::color::Color;
+use tetrust::game_mode::GameMode;
+use tetrust::settings::{self, Generator, Handling, LockReset, Opposing, Settings};
+use tetrust::shape::Shape;
+use tetrust::theme::Theme;
+
+// building blocks shared by the replay and saved game formats, numbers are
+// LEB128 varints and signed numbers are zigzag encoded first
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    Magic,
+    Version(u8),
+    Truncated,
+    Invalid,
+    Base64,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            DecodeError::Magic => write!(f, "unrecognised format"),
+            DecodeError::Version(version) => write!(f, "unsupported version {}", version),
+            DecodeError::Truncated => write!(f, "data is truncated"),
+            DecodeError::Invalid => write!(f, "data is corrupt"),
+            DecodeError::Base64 => write!(f, "not valid base64"),
+        }
+    }
+}
+
+const COLORS: [Color; 11] = [
+    Color::Blue,
+    Color::BlueWhite,
+    Color::Cyan,
+    Color::CyanWhite,
+    Color::Grey,
+    Color::GreyWhite,
+    Color::Green,
+    Color::Red,
+    Color::Orange,
+    Color::Yellow,
+    Color::Purple,
+];
+
+pub fn put_varint(bytes: &mut Vec<u8>, value: u32) {
+    let mut value = value;
+
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+pub fn put_int(bytes: &mut Vec<u8>, value: i32) {
+    put_varint(bytes, ((value << 1) ^ (value >> 31)) as u32);
+}
+
+pub fn put_long(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+pub fn put_bytes(bytes: &mut Vec<u8>, value: &[u8]) {
+    put_varint(bytes, value.len() as u32);
+    bytes.extend_from_slice(value);
+}
+
+pub fn put_string(bytes: &mut Vec<u8>, value: &str) {
+    put_bytes(bytes, value.as_bytes());
+}
+
+pub fn put_shape(bytes: &mut Vec<u8>, shape: Shape) {
+    bytes.push(Shape::ALL.iter().position(|&s| s == shape).unwrap() as u8);
+}
+
+pub fn put_color(bytes: &mut Vec<u8>, color: Color) {
+    bytes.push(COLORS.iter().position(|&c| c == color).unwrap() as u8);
+}
+
+pub fn put_settings(bytes: &mut Vec<u8>, settings: &Settings) {
+    let handling = &settings.handling;
+
+    bytes.push(match settings.game_mode {
+        GameMode::Marathon => 0,
+        GameMode::Sprint => 1,
+        GameMode::Ultra => 2,
+        GameMode::Classic => 3,
+        GameMode::Finesse => 4,
+    });
+    match settings.seed {
+        Some(seed) => {
+            bytes.push(1);
+            put_varint(bytes, seed);
+        }
+        None => bytes.push(0),
+    }
+    bytes.push(settings.ghost as u8);
+    bytes.push(settings.finesse_retry as u8);
+    put_varint(bytes, settings.lock_delay);
+    match settings.lock_reset {
+        LockReset::Infinity => bytes.push(0),
+        LockReset::Step => bytes.push(1),
+        LockReset::Move(limit) => {
+            bytes.push(2);
+            put_varint(bytes, limit);
+        }
+    }
+    put_varint(bytes, settings.next_queue as u32);
+    bytes.push(match settings.theme {
+        Theme::Guideline => 0,
+        Theme::Random => 1,
+        Theme::GameBoy => 2,
+    });
+    bytes.push(match settings.randomizer {
+        Generator::Bag7 => 0,
+        Generator::Bag14 => 1,
+        Generator::Random => 2,
+        Generator::History => 3,
+    });
+
+    put_varint(bytes, handling.das);
+    put_varint(bytes, handling.arr);
+    put_varint(bytes, handling.soft_drop);
+    put_varint(bytes, handling.das_cut);
+    bytes.push(match handling.opposing {
+        Opposing::LastKeyWins => 0,
+        Opposing::Cancel => 1,
+    });
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    // checks the magic and version that every format starts with
+    pub fn new(
+        bytes: &'a [u8],
+        magic: &[u8; 4],
+        version: u8,
+    ) -> std::result::Result<Self, DecodeError> {
+        if bytes.len() < magic.len() || &bytes[..magic.len()] != magic {
+            return Err(DecodeError::Magic);
+        }
+
+        let mut reader = Reader {
+            bytes,
+            position: magic.len(),
+        };
+
+        match reader.byte()? {
+            v if v == version => Ok(reader),
+            v => Err(DecodeError::Version(v)),
+        }
+    }
+
+    // fails when bytes are left over
+    pub fn finish(&self) -> std::result::Result<(), DecodeError> {
+        if self.position == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(DecodeError::Invalid)
+        }
+    }
+
+    pub fn byte(&mut self) -> std::result::Result<u8, DecodeError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or(DecodeError::Truncated)?;
+
+        self.position += 1;
+        Ok(byte)
+    }
+
+    pub fn flag(&mut self) -> std::result::Result<bool, DecodeError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::Invalid),
+        }
+    }
+
+    pub fn varint(&mut self) -> std::result::Result<u32, DecodeError> {
+        let mut value: u32 = 0;
+
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+
+            if shift == 28 && byte > 0x0f {
+                return Err(DecodeError::Invalid);
+            }
+            value |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::Invalid)
+    }
+
+    // a varint the options menu could have produced
+    pub fn varint_in(
+        &mut self,
+        range: RangeInclusive<u32>,
+    ) -> std::result::Result<u32, DecodeError> {
+        match self.varint()? {
+            value if range.contains(&value) => Ok(value),
+            _ => Err(DecodeError::Invalid),
+        }
+    }
+
+    pub fn int(&mut self) -> std::result::Result<i32, DecodeError> {
+        let value = self.varint()?;
+
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+
+    pub fn long(&mut self) -> std::result::Result<u64, DecodeError> {
+        let mut value = [0; 8];
+
+        for byte in value.iter_mut() {
+            *byte = self.byte()?;
+        }
+        Ok(u64::from_le_bytes(value))
+    }
+
+    pub fn bytes(&mut self) -> std::result::Result<Vec<u8>, DecodeError> {
+        let len = self.varint()? as usize;
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(DecodeError::Truncated)?;
+        let bytes = self.bytes[self.position..end].to_vec();
+
+        self.position = end;
+        Ok(bytes)
+    }
+
+    pub fn string(&mut self) -> std::result::Result<String, DecodeError> {
+        String::from_utf8(self.bytes()?).map_err(|_| DecodeError::Invalid)
+    }
+
+    pub fn shape(&mut self) -> std::result::Result<Shape, DecodeError> {
+        Shape::ALL
+            .get(self.byte()? as usize)
+            .cloned()
+            .ok_or(DecodeError::Invalid)
+    }
+
+    pub fn color(&mut self) -> std::result::Result<Color, DecodeError> {
+        COLORS
+            .get(self.byte()? as usize)
+            .cloned()
+            .ok_or(DecodeError::Invalid)
+    }
+
+    pub fn settings(&mut self) -> std::result::Result<Settings, DecodeError> {
+        let game_mode = match self.byte()? {
+            0 => GameMode::Marathon,
+            1 => GameMode::Sprint,
+            2 => GameMode::Ultra,
+            3 => GameMode::Classic,
+            4 => GameMode::Finesse,
+            _ => return Err(DecodeError::Invalid),
+        };
+        let seed = match self.byte()? {
+            0 => None,
+            1 => Some(self.varint()?),
+            _ => return Err(DecodeError::Invalid),
+        };
+        let ghost = self.flag()?;
+        let finesse_retry = self.flag()?;
+        let lock_delay = self.varint_in(settings::LOCK_DELAY)?;
+        let lock_reset = match self.byte()? {
+            0 => LockReset::Infinity,
+            1 => LockReset::Step,
+            2 => LockReset::Move(self.varint_in(0..=settings::MOVE_RESETS)?),
+            _ => return Err(DecodeError::Invalid),
+        };
+        let next_queue = self.varint()? as usize;
+        if !settings::NEXT_QUEUE.contains(&next_queue) {
+            return Err(DecodeError::Invalid);
+        }
+        let theme = match self.byte()? {
+            0 => Theme::Guideline,
+            1 => Theme::Random,
+            2 => Theme::GameBoy,
+            _ => return Err(DecodeError::Invalid),
+        };
+        let randomizer = match self.byte()? {
+            0 => Generator::Bag7,
+            1 => Generator::Bag14,
+            2 => Generator::Random,
+            3 => Generator::History,
+            _ => return Err(DecodeError::Invalid),
+        };
+
+        Ok(Settings {
+            game_mode,
+            seed,
+            ghost,
+            // the stats panel is a hud preference and doesn't travel
+            stats: Settings::new().stats,
+            finesse_retry,
+            lock_delay,
+            lock_reset,
+            next_queue,
+            theme,
+            randomizer,
+            handling: Handling {
+                das: self.varint_in(settings::DAS)?,
+                arr: self.varint_in(settings::ARR)?,
+                soft_drop: self.varint_in(settings::SOFT_DROP)?,
+                das_cut: self.varint_in(settings::DAS_CUT)?,
+                opposing: match self.byte()? {
+                    0 => Opposing::LastKeyWins,
+                    1 => Opposing::Cancel,
+                    _ => return Err(DecodeError::Invalid),
+                },
+            },
+        })
+    }
+}
+
+const BASE64: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// url safe alphabet without padding so codes can be shared as links
+pub fn to_base64(bytes: &[u8]) -> String {
+    let alphabet = BASE64.as_bytes();
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+
+        for i in 0..=chunk.len() {
+            code.push(alphabet[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    code
+}
+
+pub fn from_base64(code: &str) -> std::result::Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::with_capacity(code.len() * 3 / 4);
+
+    for chunk in code.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return Err(DecodeError::Base64);
+        }
+
+        let mut bits = 0;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64
+                .bytes()
+                .position(|b| b == *c)
+                .ok_or(DecodeError::Base64)?;
+
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+
+        for i in 0..chunk.len() - 1 {
+            bytes.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
@@ -1,6 +1,7 @@
-use rand::{rngs::SmallRng, Rng};
+use grid::Tile;
+use tetrust::rng::Pcg32;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Color {
     Blue,
     BlueWhite,
@@ -8,10 +9,15 @@ pub enum Color {
     CyanWhite,
     Grey,
     GreyWhite,
+    Green,
+    Red,
+    Orange,
+    Yellow,
+    Purple,
 }
 
 impl Color {
-    pub fn rand(rng: &mut SmallRng) -> Self {
+    pub fn rand(rng: &mut Pcg32) -> Self {
         match rng.gen_range(0, 6) {
             0 => Color::Blue,
             1 => Color::BlueWhite,
@@ -21,4 +27,34 @@ impl Color {
             _ => Color::GreyWhite,
         }
     }
+
+    pub fn tile(self) -> Tile {
+        match self {
+            Color::Blue => Tile::BlueSolid,
+            Color::BlueWhite => Tile::BlueWhite,
+            Color::Cyan => Tile::CyanSolid,
+            Color::CyanWhite => Tile::CyanWhite,
+            Color::Grey => Tile::GreySolid,
+            Color::GreyWhite => Tile::GreyWhite,
+            Color::Green => Tile::GreenSolid,
+            Color::Red => Tile::RedSolid,
+            Color::Orange => Tile::OrangeSolid,
+            Color::Yellow => Tile::YellowSolid,
+            Color::Purple => Tile::PurpleSolid,
+        }
+    }
+
+    // the variant with a white centre, for atlases that shade by outline
+    pub fn white_tile(self) -> Tile {
+        match self {
+            Color::Blue | Color::BlueWhite => Tile::BlueWhite,
+            Color::Cyan | Color::CyanWhite => Tile::CyanWhite,
+            Color::Grey | Color::GreyWhite => Tile::GreyWhite,
+            Color::Green => Tile::GreenWhite,
+            Color::Red => Tile::RedWhite,
+            Color::Orange => Tile::OrangeWhite,
+            Color::Yellow => Tile::YellowWhite,
+            Color::Purple => Tile::PurpleWhite,
+        }
+    }
 }
@@ -1,4 +1,4 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Controls {
     pub up: Option<u32>,
     pub down: Option<u32>,
@@ -6,6 +6,12 @@ pub struct Controls {
     pub right: Option<u32>,
     pub primary: Option<u32>,
     pub secondary: Option<u32>,
+    pub hold: Option<u32>,
+    pub hard_drop: Option<u32>,
+    pub pause: Option<u32>,
+    pub backspace: Option<u32>,
+    // digit typed since the last frame
+    pub digit: Option<u32>,
 }
 
 impl Controls {
@@ -17,6 +23,11 @@ impl Controls {
             right: None,
             primary: None,
             secondary: None,
+            hold: None,
+            hard_drop: None,
+            pause: None,
+            backspace: None,
+            digit: None,
         }
     }
 
@@ -28,66 +39,134 @@ impl Controls {
             right: self.right.map(|x| x + 1),
             primary: self.primary.map(|x| x + 1),
             secondary: self.secondary.map(|x| x + 1),
+            hold: self.hold.map(|x| x + 1),
+            hard_drop: self.hard_drop.map(|x| x + 1),
+            pause: self.pause.map(|x| x + 1),
+            backspace: self.backspace.map(|x| x + 1),
+            digit: None,
         }
     }
 
     pub fn key_down(&mut self, key_code: u32) {
-        match key_code {
-            37 => {
-                if self.left.is_none() {
-                    self.left = Some(0)
-                }
-            }
-            38 => {
-                if self.up.is_none() {
-                    self.up = Some(0)
-                }
-            }
-            39 => {
-                if self.right.is_none() {
-                    self.right = Some(0)
-                }
-            }
-            40 => {
-                if self.down.is_none() {
-                    self.down = Some(0)
-                }
-            }
-            90 => {
-                if self.primary.is_none() {
-                    self.primary = Some(0)
-                }
-            }
-            88 => {
-                if self.secondary.is_none() {
-                    self.secondary = Some(0)
-                }
-            }
+        match (Button::from_key_code(key_code), key_code) {
+            (Some(button), _) => self.press(button),
+            (None, 48..=57) => self.digit = Some(key_code - 48),
+            (None, 96..=105) => self.digit = Some(key_code - 96),
             _ => (),
         }
     }
 
     pub fn key_up(&mut self, key_code: u32) {
-        match key_code {
-            37 => {
-                self.left = None;
-                self.right = self.right.map(|_| 1);
-            }
-            38 => {
+        if let Some(button) = Button::from_key_code(key_code) {
+            self.release(button);
+        }
+    }
+
+    pub fn press(&mut self, button: Button) {
+        let counter = self.counter_mut(button);
+
+        if counter.is_none() {
+            *counter = Some(0)
+        }
+    }
+
+    // frames the button has been held for
+    pub fn counter_mut(&mut self, button: Button) -> &mut Option<u32> {
+        match button {
+            Button::Up => &mut self.up,
+            Button::Down => &mut self.down,
+            Button::Left => &mut self.left,
+            Button::Right => &mut self.right,
+            Button::Primary => &mut self.primary,
+            Button::Secondary => &mut self.secondary,
+            Button::Hold => &mut self.hold,
+            Button::HardDrop => &mut self.hard_drop,
+            Button::Pause => &mut self.pause,
+            Button::Backspace => &mut self.backspace,
+        }
+    }
+
+    // buttons down this frame
+    pub fn held(&self) -> Vec<Button> {
+        let mut controls = self.clone();
+
+        Button::ALL
+            .iter()
+            .cloned()
+            .filter(|&button| controls.counter_mut(button).is_some())
+            .collect()
+    }
+
+    // releasing a direction restarts auto repeat of the opposite one
+    pub fn release(&mut self, button: Button) {
+        match button {
+            Button::Up => {
                 self.up = None;
                 self.down = self.down.map(|_| 1);
             }
-            39 => {
-                self.right = None;
-                self.left = self.left.map(|_| 1);
-            }
-            40 => {
+            Button::Down => {
                 self.down = None;
                 self.up = self.up.map(|_| 1);
             }
-            90 => self.primary = None,
-            88 => self.secondary = None,
-            _ => (),
+            Button::Left => {
+                self.left = None;
+                self.right = self.right.map(|_| 1);
+            }
+            Button::Right => {
+                self.right = None;
+                self.left = self.left.map(|_| 1);
+            }
+            Button::Primary => self.primary = None,
+            Button::Secondary => self.secondary = None,
+            Button::Hold => self.hold = None,
+            Button::HardDrop => self.hard_drop = None,
+            Button::Pause => self.pause = None,
+            Button::Backspace => self.backspace = None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Up,
+    Down,
+    Left,
+    Right,
+    Primary,
+    Secondary,
+    Hold,
+    HardDrop,
+    Pause,
+    Backspace,
+}
+
+impl Button {
+    pub const ALL: [Button; 10] = [
+        Button::Up,
+        Button::Down,
+        Button::Left,
+        Button::Right,
+        Button::Primary,
+        Button::Secondary,
+        Button::Hold,
+        Button::HardDrop,
+        Button::Pause,
+        Button::Backspace,
+    ];
+
+    pub fn from_key_code(key_code: u32) -> Option<Button> {
+        match key_code {
+            37 => Some(Button::Left),
+            38 => Some(Button::Up),
+            39 => Some(Button::Right),
+            40 => Some(Button::Down),
+            90 => Some(Button::Primary),
+            88 => Some(Button::Secondary),
+            16 | 67 => Some(Button::Hold),
+            32 => Some(Button::HardDrop),
+            27 | 80 => Some(Button::Pause),
+            8 => Some(Button::Backspace),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,179 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Display, Formatter, Result};
+use tetrust::piece::Piece;
+use tetrust::stack::Stack;
+
+// a single key press, das moves hold the key until the piece meets the wall
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    Left,
+    Right,
+    DasLeft,
+    DasRight,
+    RotateLeft,
+    RotateRight,
+}
+
+impl Move {
+    pub const ALL: [Move; 6] = [
+        Move::Left,
+        Move::Right,
+        Move::DasLeft,
+        Move::DasRight,
+        Move::RotateLeft,
+        Move::RotateRight,
+    ];
+
+    fn apply(self, piece: &Piece, stack: &Stack) -> Piece {
+        match self {
+            Move::Left => piece.shift(stack, 1, |piece| piece.move_left()),
+            Move::Right => piece.shift(stack, 1, |piece| piece.move_right()),
+            Move::DasLeft => piece.shift(stack, u32::MAX, |piece| piece.move_left()),
+            Move::DasRight => piece.shift(stack, u32::MAX, |piece| piece.move_right()),
+            Move::RotateLeft => piece.rotate(stack, false),
+            Move::RotateRight => piece.rotate(stack, true),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Move::Left => write!(f, "TAP L"),
+            Move::Right => write!(f, "TAP R"),
+            Move::DasLeft => write!(f, "DAS L"),
+            Move::DasRight => write!(f, "DAS R"),
+            Move::RotateLeft => write!(f, "CCW"),
+            Move::RotateRight => write!(f, "CW"),
+        }
+    }
+}
+
+// the moves pressed for a locked piece next to the shortest way there
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Judgement {
+    pub inputs: u32,
+    pub optimal: Vec<Move>,
+}
+
+impl Judgement {
+    // None when the placement can't be reached from the spawn position
+    pub fn new(inputs: u32, piece: &Piece) -> Option<Self> {
+        optimal(piece).map(|optimal| Judgement { inputs, optimal })
+    }
+
+    pub fn fault(&self) -> bool {
+        self.inputs as usize > self.optimal.len()
+    }
+}
+
+// columns and shape of the cells a piece covers, ignoring its height
+fn placement(piece: &Piece) -> Vec<(i32, i32)> {
+    let blocks = piece.blocks();
+    let top = blocks.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let mut placement: Vec<(i32, i32)> = blocks.into_iter().map(|(x, y)| (x, y - top)).collect();
+
+    placement.sort();
+    placement
+}
+
+// the fewest key presses that take a freshly spawned piece to the placement
+// of target on an empty stack before hard dropping it, breadth first
+fn optimal(target: &Piece) -> Option<Vec<Move>> {
+    let stack = Stack::new();
+    let spawned = Piece::new(target.shape, target.color).enter(&stack);
+    let goal = placement(target);
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    seen.insert((spawned.offset, spawned.orientation));
+    queue.push_back((spawned, vec![]));
+
+    while let Some((piece, moves)) = queue.pop_front() {
+        if placement(&piece) == goal {
+            return Some(moves);
+        }
+
+        for &m in Move::ALL.iter() {
+            let moved = m.apply(&piece, &stack);
+
+            if seen.insert((moved.offset, moved.orientation)) {
+                let mut moves = moves.clone();
+
+                moves.push(m);
+                queue.push_back((moved, moves));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetrust::color::Color;
+    use tetrust::shape::Shape;
+
+    // a piece resting in the middle of an empty stack, x is its offset
+    fn placed(shape: Shape, orientation: usize, x: i32) -> Piece {
+        let spawned = Piece::new(shape, Color::Grey).enter(&Stack::new());
+        let piece = Piece {
+            orientation,
+            offset: (x, spawned.offset.1 + 10),
+            ..spawned
+        };
+
+        assert!(piece.fits(&Stack::new()));
+        piece
+    }
+
+    fn spawn_x(shape: Shape) -> i32 {
+        Piece::new(shape, Color::Grey).offset.0
+    }
+
+    #[test]
+    fn finds_the_fewest_presses() {
+        assert_eq!(optimal(&placed(Shape::O, 0, 0)), Some(vec![Move::DasLeft]));
+        assert_eq!(
+            optimal(&placed(Shape::T, 0, spawn_x(Shape::T))),
+            Some(vec![])
+        );
+        assert_eq!(
+            optimal(&placed(Shape::I, 1, 7)).map(|moves| moves.len()),
+            Some(2)
+        );
+        assert_eq!(
+            optimal(&placed(Shape::T, 2, spawn_x(Shape::T))).map(|moves| moves.len()),
+            Some(2)
+        );
+    }
+
+    // orientations covering the same cells are the same placement
+    #[test]
+    fn treats_symmetric_orientations_alike() {
+        let s = spawn_x(Shape::S);
+        let i = spawn_x(Shape::I);
+
+        assert_eq!(optimal(&placed(Shape::I, 2, i)), Some(vec![]));
+        assert_eq!(
+            optimal(&placed(Shape::S, 3, s + 1)),
+            optimal(&placed(Shape::S, 1, s))
+        );
+        assert_eq!(
+            optimal(&placed(Shape::S, 1, s)).map(|moves| moves.len()),
+            Some(1)
+        );
+        assert_eq!(
+            optimal(&placed(Shape::Z, 3, s + 1)).map(|moves| moves.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn judges_extra_presses_as_faults() {
+        let piece = placed(Shape::O, 0, 0);
+
+        assert!(!Judgement::new(1, &piece).unwrap().fault());
+        assert!(Judgement::new(2, &piece).unwrap().fault());
+    }
+}
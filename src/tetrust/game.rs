@@ -1,16 +1,127 @@
 use grid::{Pos, Tile};
-use rand::{rngs::SmallRng, SeedableRng};
+use std::fmt::{Display, Formatter, Result};
+use std::ops::RangeInclusive;
 use tetrust::controls::Controls;
-use tetrust::menu::Menu;
-use tetrust::piece::{Grid, Piece};
-use tetrust::playing::{Level, Mode, Playing};
+use tetrust::game_mode::GameMode;
+use tetrust::menu::{Menu, PauseMenu, ResultsMenu};
+use tetrust::piece::Grid;
+use tetrust::playing::{GameOverReason, Level, Mode, Playing};
+use tetrust::settings::{self, Handling, Settings, StatsPanel};
 use GRID_HEIGHT;
 use GRID_WIDTH;
 
+#[derive(Clone, Copy, Debug)]
+pub enum Pause {
+    Menu(PauseMenu),
+    Countdown(u32),
+}
+
 #[derive(Clone, Debug)]
 pub enum Game {
-    InMenu(Menu),
+    // a suspended game that can be continued from the menu
+    InMenu(Menu, Settings, Option<Playing>),
+    Countdown(Playing, u32),
     Playing(Playing),
+    Paused(Playing, Pause),
+    Over(Playing, u32),
+    Results(Playing, ResultsMenu),
+}
+
+// lifecycle transitions reported to the page
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Ready,
+    Go,
+    Paused,
+    Resumed,
+    GameOver(GameOverReason),
+    Results,
+    // the finished game placed on its mode's board
+    NewHighScore,
+    Replay,
+    HighScores,
+    Quit,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Event::Ready => write!(f, "ready"),
+            Event::Go => write!(f, "go"),
+            Event::Paused => write!(f, "paused"),
+            Event::Resumed => write!(f, "resumed"),
+            Event::GameOver(reason) => write!(
+                f,
+                "game_over:{}",
+                match reason {
+                    GameOverReason::BlockOut => "block_out",
+                    GameOverReason::LockOut => "lock_out",
+                    GameOverReason::GoalReached => "goal_reached",
+                    GameOverReason::TimeExpired => "time_expired",
+                }
+            ),
+            Event::Results => write!(f, "results"),
+            Event::NewHighScore => write!(f, "new_high_score"),
+            Event::Replay => write!(f, "replay"),
+            Event::HighScores => write!(f, "high_scores"),
+            Event::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+// steps a numeric option with left/right, repeating while held
+fn adjust(controls: &Controls, value: u32, range: RangeInclusive<u32>) -> Option<u32> {
+    let (min, max) = range.into_inner();
+
+    if input_trigger(controls.left, controls.right, 30, 5) && value > min {
+        Some(value - 1)
+    } else if input_trigger(controls.right, controls.left, 30, 5) && value < max {
+        Some(value + 1)
+    } else {
+        None
+    }
+}
+
+fn adjust_handling(controls: &Controls, menu: &Menu, handling: Handling) -> Option<Handling> {
+    match menu {
+        Menu::Das => {
+            adjust(controls, handling.das, settings::DAS).map(|das| Handling { das, ..handling })
+        }
+        Menu::Arr => {
+            adjust(controls, handling.arr, settings::ARR).map(|arr| Handling { arr, ..handling })
+        }
+        Menu::SoftDrop => {
+            adjust(controls, handling.soft_drop, settings::SOFT_DROP).map(|soft_drop| Handling {
+                soft_drop,
+                ..handling
+            })
+        }
+        Menu::DasCut => {
+            adjust(controls, handling.das_cut, settings::DAS_CUT).map(|das_cut| Handling {
+                das_cut,
+                ..handling
+            })
+        }
+        Menu::Opposing if controls.left == Some(0) || controls.right == Some(0) => Some(Handling {
+            opposing: handling.opposing.next(),
+            ..handling
+        }),
+        _ => None,
+    }
+}
+
+// digits append to the seed and backspace removes one, back to random
+fn type_seed(seed: Option<u32>, controls: &Controls) -> Option<u32> {
+    match (seed, controls.digit) {
+        (Some(seed), Some(digit)) => Some(
+            seed.checked_mul(10)
+                .and_then(|seed| seed.checked_add(digit))
+                .unwrap_or(seed),
+        ),
+        (None, Some(digit)) => Some(digit),
+        (Some(seed), None) if seed >= 10 => Some(seed / 10),
+        _ => None,
+    }
 }
 
 pub fn input_trigger(
@@ -34,35 +145,363 @@ pub fn input_trigger(
 }
 
 impl Game {
-    pub fn iterate(&self, controls: &Controls) -> Self {
+    // entropy seeds the next game when no seed has been chosen
+    pub fn iterate(&self, controls: &Controls, entropy: u32) -> (Self, Option<Event>) {
         match self {
-            Game::InMenu(menu) => match menu {
-                Menu::NewGame => {
-                    if controls.primary.is_some() {
-                        let mut rng =
-                            SmallRng::from_seed([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-
-                        Game::Playing(Playing::new(Level::new(0), &rng))
-                    } else if input_trigger(controls.down, controls.up, 30, 10)
-                        || input_trigger(controls.up, controls.down, 30, 10)
-                    {
-                        Game::InMenu(Menu::Options)
+            Game::InMenu(Menu::NewGame, settings, _) if controls.primary == Some(0) => {
+                (Game::new_game(settings, entropy), Some(Event::Ready))
+            }
+            Game::InMenu(Menu::Continue, _, Some(playing)) if controls.primary == Some(0) => {
+                (Game::resume(playing), Some(Event::Ready))
+            }
+            // the regulator holds the tables and shows the board
+            Game::InMenu(Menu::HighScores, _, _) if controls.primary == Some(0) => {
+                (self.clone(), Some(Event::HighScores))
+            }
+            Game::InMenu(menu, settings, saved) => (
+                {
+                    // continue is only offered while there is a saved game
+                    let skip = |menu: Menu, step: fn(&Menu) -> Menu| match menu {
+                        Menu::Continue if saved.is_none() => step(&menu),
+                        _ => menu,
+                    };
+
+                    if input_trigger(controls.down, controls.up, 30, 10) {
+                        Game::InMenu(
+                            skip(menu.next(), Menu::next),
+                            settings.clone(),
+                            saved.clone(),
+                        )
+                    } else if input_trigger(controls.up, controls.down, 30, 10) {
+                        Game::InMenu(
+                            skip(menu.previous(), Menu::previous),
+                            settings.clone(),
+                            saved.clone(),
+                        )
                     } else {
-                        Game::InMenu(Menu::NewGame)
+                        match menu {
+                            Menu::GameMode if controls.left == Some(0) => Game::InMenu(
+                                Menu::GameMode,
+                                Settings {
+                                    game_mode: settings.game_mode.previous(),
+                                    ..settings.clone()
+                                },
+                                saved.clone(),
+                            ),
+                            Menu::GameMode if controls.right == Some(0) => Game::InMenu(
+                                Menu::GameMode,
+                                Settings {
+                                    game_mode: settings.game_mode.next(),
+                                    ..settings.clone()
+                                },
+                                saved.clone(),
+                            ),
+                            Menu::Seed
+                                if controls.digit.is_some() || controls.backspace == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::Seed,
+                                    Settings {
+                                        seed: type_seed(settings.seed, controls),
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::Options if controls.primary == Some(0) => {
+                                Game::InMenu(Menu::Ghost, settings.clone(), saved.clone())
+                            }
+                            Menu::Ghost
+                                if controls.left == Some(0) || controls.right == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::Ghost,
+                                    Settings {
+                                        ghost: !settings.ghost,
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::Stats
+                                if controls.left == Some(0) || controls.right == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::Stats,
+                                    Settings {
+                                        stats: settings.stats.next(),
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::FinesseRetry
+                                if controls.left == Some(0) || controls.right == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::FinesseRetry,
+                                    Settings {
+                                        finesse_retry: !settings.finesse_retry,
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::LockDelay => {
+                                match adjust(controls, settings.lock_delay, settings::LOCK_DELAY) {
+                                    Some(lock_delay) => Game::InMenu(
+                                        Menu::LockDelay,
+                                        Settings {
+                                            lock_delay,
+                                            ..settings.clone()
+                                        },
+                                        saved.clone(),
+                                    ),
+                                    None => self.clone(),
+                                }
+                            }
+                            Menu::LockReset
+                                if controls.left == Some(0) || controls.right == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::LockReset,
+                                    Settings {
+                                        lock_reset: settings.lock_reset.next(),
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::NextQueue if controls.left == Some(0) => Game::InMenu(
+                                Menu::NextQueue,
+                                Settings {
+                                    next_queue: (settings.next_queue - 1)
+                                        .max(*settings::NEXT_QUEUE.start()),
+                                    ..settings.clone()
+                                },
+                                saved.clone(),
+                            ),
+                            Menu::NextQueue if controls.right == Some(0) => Game::InMenu(
+                                Menu::NextQueue,
+                                Settings {
+                                    next_queue: (settings.next_queue + 1)
+                                        .min(*settings::NEXT_QUEUE.end()),
+                                    ..settings.clone()
+                                },
+                                saved.clone(),
+                            ),
+                            Menu::Theme
+                                if controls.left == Some(0) || controls.right == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::Theme,
+                                    Settings {
+                                        theme: settings.theme.next(),
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::Randomizer
+                                if controls.left == Some(0) || controls.right == Some(0) =>
+                            {
+                                Game::InMenu(
+                                    Menu::Randomizer,
+                                    Settings {
+                                        randomizer: settings.randomizer.next(),
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                )
+                            }
+                            Menu::Back if controls.primary == Some(0) => {
+                                Game::InMenu(Menu::Options, settings.clone(), saved.clone())
+                            }
+                            _ => match adjust_handling(controls, menu, settings.handling) {
+                                Some(handling) => Game::InMenu(
+                                    menu.clone(),
+                                    Settings {
+                                        handling,
+                                        ..settings.clone()
+                                    },
+                                    saved.clone(),
+                                ),
+                                None => self.clone(),
+                            },
+                        }
                     }
-                }
+                },
+                None,
+            ),
+            Game::Countdown(playing, 1) => (Game::Playing(playing.clone()), Some(Event::Go)),
+            Game::Countdown(playing, timer) => (Game::Countdown(playing.clone(), timer - 1), None),
+            Game::Playing(_) if controls.pause == Some(0) => self.pause(),
+            Game::Playing(playing) => {
+                let playing = playing.iterate(controls);
 
-                Menu::Options => {
-                    if input_trigger(controls.up, controls.down, 30, 10)
-                        || input_trigger(controls.down, controls.up, 30, 10)
-                    {
-                        Game::InMenu(Menu::NewGame)
-                    } else {
-                        Game::InMenu(Menu::Options)
+                match playing.game_over {
+                    Some(reason) => (Game::Over(playing, 120), Some(Event::GameOver(reason))),
+                    None => (Game::Playing(playing), None),
+                }
+            }
+            Game::Paused(playing, pause) => Game::unpause(playing, *pause, controls, entropy),
+            Game::Over(playing, timer) if *timer == 1 || controls.primary == Some(0) => (
+                Game::Results(playing.clone(), ResultsMenu::Retry),
+                Some(Event::Results),
+            ),
+            Game::Over(playing, timer) => (Game::Over(playing.clone(), timer - 1), None),
+            Game::Results(playing, item) => {
+                if input_trigger(controls.down, controls.up, 30, 10) {
+                    (Game::Results(playing.clone(), item.next()), None)
+                } else if input_trigger(controls.up, controls.down, 30, 10) {
+                    (Game::Results(playing.clone(), item.previous()), None)
+                } else if controls.primary == Some(0) {
+                    match item {
+                        ResultsMenu::Retry => (
+                            Game::new_game(&playing.settings, entropy),
+                            Some(Event::Ready),
+                        ),
+                        // the regulator holds the recording and starts the player
+                        ResultsMenu::Replay => (self.clone(), Some(Event::Replay)),
+                        ResultsMenu::MainMenu => (
+                            Game::InMenu(Menu::NewGame, playing.settings.clone(), None),
+                            Some(Event::Quit),
+                        ),
                     }
+                } else {
+                    (self.clone(), None)
                 }
-            },
-            Game::Playing(playing) => Game::Playing(playing.iterate(&controls)),
+            }
+        }
+    }
+
+    fn unpause(
+        playing: &Playing,
+        pause: Pause,
+        controls: &Controls,
+        entropy: u32,
+    ) -> (Self, Option<Event>) {
+        match pause {
+            Pause::Menu(item)
+                if controls.pause == Some(0)
+                    || (item == PauseMenu::Resume && controls.primary == Some(0)) =>
+            {
+                (Game::Paused(playing.clone(), Pause::Countdown(180)), None)
+            }
+            Pause::Menu(PauseMenu::Restart) if controls.primary == Some(0) => (
+                Game::new_game(&playing.settings, entropy),
+                Some(Event::Ready),
+            ),
+            Pause::Menu(PauseMenu::Quit) if controls.primary == Some(0) => (
+                Game::InMenu(Menu::NewGame, playing.settings.clone(), None),
+                Some(Event::Quit),
+            ),
+            Pause::Menu(item) if input_trigger(controls.down, controls.up, 30, 10) => (
+                Game::Paused(playing.clone(), Pause::Menu(item.next())),
+                None,
+            ),
+            Pause::Menu(item) if input_trigger(controls.up, controls.down, 30, 10) => (
+                Game::Paused(playing.clone(), Pause::Menu(item.previous())),
+                None,
+            ),
+            Pause::Menu(_) => (Game::Paused(playing.clone(), pause), None),
+            Pause::Countdown(1) => (Game::Playing(playing.clone()), Some(Event::Resumed)),
+            Pause::Countdown(timer) => (
+                Game::Paused(playing.clone(), Pause::Countdown(timer - 1)),
+                None,
+            ),
+        }
+    }
+
+    fn new_game(settings: &Settings, entropy: u32) -> Self {
+        Game::start(settings, settings.seed.unwrap_or(entropy))
+    }
+
+    pub fn start(settings: &Settings, seed: u32) -> Self {
+        Game::Countdown(
+            Playing::new(
+                Level::new(0),
+                seed,
+                settings.randomizer.randomizer(),
+                settings,
+            ),
+            120,
+        )
+    }
+
+    // a saved game picks up after the resume countdown
+    pub fn resume(playing: &Playing) -> Self {
+        Game::Paused(playing.clone(), Pause::Countdown(180))
+    }
+
+    pub fn with_seed(&self, seed: Option<u32>) -> Self {
+        match self {
+            Game::InMenu(menu, settings, saved) => Game::InMenu(
+                menu.clone(),
+                Settings {
+                    seed,
+                    ..settings.clone()
+                },
+                saved.clone(),
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    // saves leave hud preferences out, the menu's are kept
+    pub fn with_saved(&self, playing: Playing) -> Self {
+        match self {
+            Game::InMenu(_, settings, _) => {
+                let settings = Settings {
+                    stats: settings.stats,
+                    ..playing.settings.clone()
+                };
+
+                Game::InMenu(
+                    Menu::Continue,
+                    settings.clone(),
+                    Some(Playing {
+                        settings,
+                        ..playing
+                    }),
+                )
+            }
+            _ => self.clone(),
+        }
+    }
+
+    // the game that would be lost if the page closed now
+    pub fn saved(&self) -> Option<&Playing> {
+        match self {
+            Game::InMenu(_, _, saved) => saved.as_ref(),
+            Game::Countdown(playing, _) | Game::Playing(playing) | Game::Paused(playing, _) => {
+                Some(playing)
+            }
+            Game::Over(..) | Game::Results(..) => None,
+        }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        match self {
+            Game::InMenu(_, settings, _) => settings,
+            Game::Countdown(playing, _)
+            | Game::Playing(playing)
+            | Game::Paused(playing, _)
+            | Game::Over(playing, _)
+            | Game::Results(playing, _) => &playing.settings,
+        }
+    }
+
+    // countdowns are paused too so play never starts while the page is away
+    pub fn pause(&self) -> (Self, Option<Event>) {
+        match self {
+            Game::Countdown(playing, _)
+            | Game::Playing(playing)
+            | Game::Paused(playing, Pause::Countdown(_)) => (
+                Game::Paused(playing.clone(), Pause::Menu(PauseMenu::Resume)),
+                Some(Event::Paused),
+            ),
+            _ => (self.clone(), None),
         }
     }
 
@@ -70,54 +509,340 @@ impl Game {
         let mut grid = Grid::new(GRID_WIDTH, GRID_HEIGHT);
 
         match self {
-            Game::InMenu(menu) => {
-                grid.draw_rect(14, 1, 20, GRID_HEIGHT - 2, Tile::Space);
-                grid.draw_string(Pos(21, 3), "Tet-Rust");
-                grid.draw_string(Pos(19, 7), "Start Game");
-                grid.draw_string(Pos(19, 9), "Options");
-                match menu {
-                    Menu::NewGame => grid[Pos(18, 7)] = Tile::Asterisk,
-                    Menu::Options => grid[Pos(18, 9)] = Tile::Asterisk,
+            Game::InMenu(menu, settings, saved) => {
+                grid.draw_rect(12, 1, 24, GRID_HEIGHT - 2, Tile::Space);
+
+                let (title, items) = match menu {
+                    Menu::Continue
+                    | Menu::NewGame
+                    | Menu::GameMode
+                    | Menu::Seed
+                    | Menu::HighScores
+                    | Menu::Options => (
+                        "Tet-Rust",
+                        saved
+                            .iter()
+                            .map(|_| (Menu::Continue, "Continue".to_string()))
+                            .chain(vec![
+                                (Menu::NewGame, "Start Game".to_string()),
+                                (Menu::GameMode, format!("Mode: {}", settings.game_mode)),
+                                (
+                                    Menu::Seed,
+                                    match settings.seed {
+                                        Some(seed) => format!("Seed: {}", seed),
+                                        None => "Seed: Random".to_string(),
+                                    },
+                                ),
+                                (Menu::HighScores, "High Scores".to_string()),
+                                (Menu::Options, "Options".to_string()),
+                            ])
+                            .collect(),
+                    ),
+                    _ => (
+                        "Options",
+                        vec![
+                            (
+                                Menu::Ghost,
+                                format!("Ghost: {}", if settings.ghost { "On" } else { "Off" }),
+                            ),
+                            (Menu::Stats, format!("Stats: {}", settings.stats)),
+                            (
+                                Menu::FinesseRetry,
+                                format!(
+                                    "Finesse Retry: {}",
+                                    if settings.finesse_retry { "On" } else { "Off" }
+                                ),
+                            ),
+                            (
+                                Menu::LockDelay,
+                                format!("Lock Delay: {}", settings.lock_delay),
+                            ),
+                            (
+                                Menu::LockReset,
+                                format!("Lock Reset: {}", settings.lock_reset),
+                            ),
+                            (
+                                Menu::NextQueue,
+                                format!("Next Queue: {}", settings.next_queue),
+                            ),
+                            (Menu::Theme, format!("Theme: {}", settings.theme)),
+                            (
+                                Menu::Randomizer,
+                                format!("Randomizer: {}", settings.randomizer),
+                            ),
+                            (Menu::Das, format!("DAS: {}", settings.handling.das)),
+                            (
+                                Menu::Arr,
+                                match settings.handling.arr {
+                                    0 => "ARR: Instant".to_string(),
+                                    arr => format!("ARR: {}", arr),
+                                },
+                            ),
+                            (
+                                Menu::SoftDrop,
+                                match settings.handling.soft_drop {
+                                    0 => "Soft Drop: Instant".to_string(),
+                                    factor => format!("Soft Drop: x{}", factor),
+                                },
+                            ),
+                            (
+                                Menu::DasCut,
+                                format!("DAS Cut: {}", settings.handling.das_cut),
+                            ),
+                            (
+                                Menu::Opposing,
+                                format!("Opposing: {}", settings.handling.opposing),
+                            ),
+                            (Menu::Back, "Back".to_string()),
+                        ],
+                    ),
+                };
+
+                grid.draw_string(Pos(21, 3), title);
+
+                let spacing = if items.len() > 5 { 1 } else { 2 };
+
+                for (i, (item, label)) in items.iter().enumerate() {
+                    let y = 7 + spacing * i as u32;
+
+                    grid.draw_string(Pos(15, y), label);
+                    if item == menu {
+                        grid[Pos(14, y)] = Tile::Asterisk;
+                    }
+                }
+            }
+            Game::Countdown(playing, timer) => {
+                draw_hud(&mut grid, playing);
+                grid.draw_stack(&playing.stack, 0, 14, 4, playing.settings.theme);
+                if *timer > 60 {
+                    grid.draw_string(Pos(17, 13), "READY");
+                } else {
+                    grid.draw_string(Pos(18, 13), "GO");
                 }
             }
             Game::Playing(playing) => {
-                grid.draw_rect(25, 4, 9, 6, Tile::Space);
-                grid.draw_rect(25, 11, 9, 6, Tile::Space);
-                grid.draw_rect(25, 18, 9, 6, Tile::Space);
-                grid.draw_rect(14, 1, 20, 2, Tile::Space);
+                draw_hud(&mut grid, playing);
+                draw_mode(&mut grid, playing);
+            }
+            Game::Paused(playing, Pause::Menu(item)) => {
+                draw_hud(&mut grid, playing);
+                grid.draw_string(Pos(16, 8), "Paused");
 
-                grid.draw_string(Pos(21, 1), "Tet-Rust");
+                for (i, (pause_item, label)) in [
+                    (PauseMenu::Resume, "Resume"),
+                    (PauseMenu::Restart, "Restart"),
+                    (PauseMenu::Quit, "Quit"),
+                ]
+                .iter()
+                .enumerate()
+                {
+                    let y = 11 + 2 * i as u32;
 
-                match &playing.mode {
-                    Mode::DissolvingRows(timer) => {
-                        grid.draw_stack(&playing.stack, *timer, 14, 4);
-                    }
-                    Mode::DroppingPiece(piece) => {
-                        grid.draw_stack(&playing.stack, 0, 14, 4);
-                        grid.draw_piece(piece, 14, 4);
+                    grid.draw_string(Pos(16, y), label);
+                    if pause_item == item {
+                        grid[Pos(15, y)] = Tile::Asterisk;
                     }
                 }
+            }
+            Game::Paused(playing, Pause::Countdown(timer)) => {
+                draw_hud(&mut grid, playing);
+                draw_mode(&mut grid, playing);
+                grid.draw_string(Pos(18, 13), &format!("{}", timer.div_ceil(60)));
+            }
+            Game::Over(playing, _) => {
+                draw_hud(&mut grid, playing);
+                draw_mode(&mut grid, playing);
+                if let Some(reason) = playing.game_over {
+                    grid.draw_string(Pos(15, 13), &format!("{}", reason));
+                }
+            }
+            Game::Results(playing, item) => {
+                grid.draw_rect(12, 1, 24, GRID_HEIGHT - 2, Tile::Space);
+                grid.draw_string(Pos(21, 3), "Results");
 
-                grid.draw_string(Pos(27, 4), "Next");
-                grid.draw_piece(&Piece::new(playing.shape, playing.color), 24, 6);
-
-                grid.draw_string(Pos(25, 18), &format!("{}", playing.time_elapsed));
-                grid.draw_string(Pos(25, 19), &format!("{}", playing.pieces_dropped));
-                grid.draw_string(Pos(25, 20), &format!("{}", playing.rows_removed));
-                grid.draw_string(Pos(25, 21), &format!("{}", playing.score));
+                if let Some(reason) = playing.game_over {
+                    grid.draw_string(Pos(15, 6), &format!("{}", reason));
+                }
+                grid.draw_string(Pos(15, 7), &format!("{}", playing.settings.game_mode));
+                grid.draw_string(Pos(15, 9), &format!("{}", playing.score));
+                grid.draw_string(Pos(15, 10), &format!("{}", playing.rows_removed));
+                grid.draw_string(Pos(15, 11), &format!("{}", playing.pieces_dropped));
                 grid.draw_string(
-                    Pos(25, 22),
-                    &format!(
-                        "{}",
-                        playing.base_level.adjusted_level(playing.rows_removed)
-                    ),
+                    Pos(15, 12),
+                    &format!("PPS:{:.2}", playing.pieces_per_second()),
+                );
+                grid.draw_string(Pos(15, 13), &format!("{}", playing.time_elapsed));
+                grid.draw_string(Pos(15, 14), &format!("{}", playing.perfect_clears));
+                grid.draw_string(Pos(15, 15), &format!("SEED:{}", playing.seed));
+                grid.draw_string(Pos(26, 9), &format!("{}", playing.attack));
+                grid.draw_string(
+                    Pos(26, 10),
+                    &format!("APM:{:.1}", playing.attack_per_minute()),
                 );
+                grid.draw_string(
+                    Pos(26, 11),
+                    &format!("LPM:{:.1}", playing.lines_per_minute()),
+                );
+                grid.draw_string(Pos(26, 12), &format!("KPP:{:.2}", playing.keys_per_piece()));
+                grid.draw_string(Pos(26, 13), &format!("{}", playing.finesse_faults));
 
-                if playing.game_over {
-                    grid.draw_string(Pos(15, 13), "GAME OVER");
+                for (i, (results_item, label)) in [
+                    (ResultsMenu::Retry, "Retry"),
+                    (ResultsMenu::Replay, "Watch Replay"),
+                    (ResultsMenu::MainMenu, "Main Menu"),
+                ]
+                .iter()
+                .enumerate()
+                {
+                    let y = 17 + 2 * i as u32;
+
+                    grid.draw_string(Pos(15, y), label);
+                    if results_item == item {
+                        grid[Pos(14, y)] = Tile::Asterisk;
+                    }
                 }
             }
         }
         grid
     }
 }
+
+fn draw_hud(grid: &mut Grid, playing: &Playing) {
+    grid.draw_rect(5, 4, 9, 6, Tile::Space);
+    grid.draw_rect(25, 4, 9, 2 + 3 * playing.next.len() as u32, Tile::Space);
+    grid.draw_rect(3, 11, 11, 4, Tile::Space);
+    grid.draw_rect(3, 16, 11, 8, Tile::Space);
+    grid.draw_rect(14, 1, 20, 2, Tile::Space);
+    grid.draw_rect(14, 4, 10, 20, Tile::Space);
+
+    grid.draw_string(Pos(21, 1), "Tet-Rust");
+
+    grid.draw_string(Pos(27, 4), "Next");
+    for (i, piece) in playing.next.iter().enumerate() {
+        grid.draw_preview(piece, 25, 6 + 3 * i as u32, playing.settings.theme);
+    }
+
+    grid.draw_string(Pos(7, 4), "Hold");
+    if let Some(held) = &playing.hold {
+        grid.draw_preview(held, 5, 6, playing.settings.theme);
+    }
+
+    if let Some(banner) = &playing.banner {
+        for (i, line) in banner.lines.iter().take(4).enumerate() {
+            grid.draw_string(Pos(3, 11 + i as u32), line);
+        }
+    }
+
+    grid.draw_string(Pos(4, 16), &format!("{}", playing.time_elapsed));
+    grid.draw_string(Pos(4, 17), &format!("{}", playing.pieces_dropped));
+    grid.draw_string(Pos(4, 18), &format!("{}", playing.rows_removed));
+    grid.draw_string(Pos(4, 19), &format!("{}", playing.score));
+    grid.draw_string(
+        Pos(4, 20),
+        &format!(
+            "{}",
+            playing.base_level.adjusted_level(playing.rows_removed)
+        ),
+    );
+    grid.draw_string(Pos(4, 21), &format!("{}", playing.combo));
+    grid.draw_string(Pos(4, 22), &format!("{}", playing.perfect_clears));
+    if playing.back_to_back {
+        grid.draw_string(Pos(4, 23), "B2B");
+    }
+
+    draw_stats(grid, playing);
+    if playing.settings.game_mode == GameMode::Finesse {
+        draw_judgement(grid, playing);
+    }
+}
+
+// the last placement and the shortest way to make it, ending in a hard drop
+fn draw_judgement(grid: &mut Grid, playing: &Playing) {
+    let lines = match &playing.judgement {
+        Some(judgement) => vec![
+            if judgement.fault() { "FAULT" } else { "CLEAN" }.to_string(),
+            format!("KEYS:{}/{}", judgement.inputs, judgement.optimal.len()),
+        ]
+        .into_iter()
+        .chain(judgement.optimal.iter().map(|m| format!("{}", m)))
+        .chain(vec!["DROP".to_string()])
+        .collect(),
+        None => vec!["NOT JUDGED".to_string()],
+    };
+
+    grid.draw_rect(35, 12, 11, 2 + lines.len() as u32, Tile::Space);
+    grid.draw_string(Pos(37, 12), "Finesse");
+    for (i, line) in lines.iter().enumerate() {
+        grid.draw_string(Pos(36, 14 + i as u32), line);
+    }
+}
+
+fn draw_stats(grid: &mut Grid, playing: &Playing) {
+    let mut lines = vec![
+        format!("PPS:{:.2}", playing.pieces_per_second()),
+        format!("LPM:{:.1}", playing.lines_per_minute()),
+        format!("APM:{:.1}", playing.attack_per_minute()),
+    ];
+
+    match playing.settings.stats {
+        StatsPanel::Off => return,
+        StatsPanel::Pace => (),
+        StatsPanel::Full => {
+            lines.push(format!("KPP:{:.2}", playing.keys_per_piece()));
+            lines.push(format!("{}", playing.finesse_faults));
+        }
+    }
+
+    grid.draw_rect(35, 4, 11, 2 + lines.len() as u32, Tile::Space);
+    grid.draw_string(Pos(38, 4), "Stats");
+    for (i, line) in lines.iter().enumerate() {
+        grid.draw_string(Pos(36, 6 + i as u32), line);
+    }
+}
+
+fn draw_mode(grid: &mut Grid, playing: &Playing) {
+    let theme = playing.settings.theme;
+
+    match &playing.mode {
+        Mode::DissolvingRows(timer, _) => {
+            grid.draw_stack(&playing.stack, *timer, 14, 4, theme);
+        }
+        Mode::DroppingPiece(piece, _) => {
+            grid.draw_stack(&playing.stack, 0, 14, 4, theme);
+            if playing.settings.ghost {
+                grid.draw_ghost(&piece.drop(&playing.stack), 14, 4);
+            }
+            grid.draw_piece(piece, 14, 4, theme);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_during_countdowns() {
+        let settings = Settings::new();
+        let countdown = Game::start(&settings, 1);
+        let playing = match &countdown {
+            Game::Countdown(playing, _) => playing.clone(),
+            _ => panic!("games start with a countdown"),
+        };
+
+        for game in [
+            countdown,
+            Game::Playing(playing.clone()),
+            Game::resume(&playing),
+        ] {
+            match game.pause() {
+                (Game::Paused(_, Pause::Menu(PauseMenu::Resume)), Some(Event::Paused)) => (),
+                paused => panic!("{:?} was not paused", paused.1),
+            }
+        }
+
+        let menu = Game::Paused(playing, Pause::Menu(PauseMenu::Quit));
+
+        assert_eq!(menu.pause().1, None);
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::{Display, Formatter, Result};
+use tetrust::high_scores::Metric;
+use tetrust::scoring::{Classic, Guideline, Linear, ScoringRules};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    Marathon,
+    Sprint,
+    Ultra,
+    Classic,
+    // untimed practice that judges the finesse of every piece
+    Finesse,
+}
+
+impl GameMode {
+    pub fn next(self) -> Self {
+        match self {
+            GameMode::Marathon => GameMode::Sprint,
+            GameMode::Sprint => GameMode::Ultra,
+            GameMode::Ultra => GameMode::Classic,
+            GameMode::Classic => GameMode::Finesse,
+            GameMode::Finesse => GameMode::Marathon,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            GameMode::Marathon => GameMode::Finesse,
+            GameMode::Finesse => GameMode::Classic,
+            GameMode::Sprint => GameMode::Marathon,
+            GameMode::Ultra => GameMode::Sprint,
+            GameMode::Classic => GameMode::Ultra,
+        }
+    }
+
+    // rows to clear before the game is won
+    pub fn line_goal(self) -> Option<u32> {
+        match self {
+            GameMode::Marathon => Some(150),
+            GameMode::Sprint => Some(40),
+            GameMode::Ultra | GameMode::Classic | GameMode::Finesse => None,
+        }
+    }
+
+    pub fn scoring_rules(self) -> Box<dyn ScoringRules> {
+        match self {
+            GameMode::Marathon | GameMode::Ultra | GameMode::Finesse => Box::new(Guideline),
+            GameMode::Sprint => Box::new(Linear),
+            GameMode::Classic => Box::new(Classic),
+        }
+    }
+
+    // what high scores are ranked by
+    pub fn metric(self) -> Metric {
+        match self {
+            GameMode::Marathon | GameMode::Ultra | GameMode::Finesse => Metric::Score,
+            GameMode::Sprint => Metric::Time,
+            GameMode::Classic => Metric::Lines,
+        }
+    }
+
+    // frames before the game ends
+    pub fn time_limit(self) -> Option<u32> {
+        match self {
+            GameMode::Ultra => Some(3 * 60 * 60),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            GameMode::Marathon => write!(f, "Marathon"),
+            GameMode::Sprint => write!(f, "Sprint"),
+            GameMode::Ultra => write!(f, "Ultra"),
+            GameMode::Classic => write!(f, "Classic"),
+            GameMode::Finesse => write!(f, "Finesse"),
+        }
+    }
+}
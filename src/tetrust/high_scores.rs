@@ -0,0 +1,387 @@
+use grid::{Pos, Tile};
+#[cfg(test)]
+use std::collections::HashMap;
+use std::fmt::Debug;
+use tetrust::codec::{self, put_string, put_varint, DecodeError, Reader};
+use tetrust::game_mode::GameMode;
+use tetrust::piece::Grid;
+use tetrust::playing::{GameOverReason, Playing};
+use wasm_bindgen::prelude::*;
+use GRID_HEIGHT;
+
+// results kept per mode
+const CAPACITY: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Score,
+    Time,
+    Lines,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    // days since 1970-01-01
+    pub date: u32,
+    pub seed: u32,
+    pub score: u32,
+    pub rows: u32,
+    pub pieces: u32,
+    pub frames: u32,
+}
+
+impl Entry {
+    // None when the game doesn't count, like a sprint that never finished
+    pub fn new(name: &str, date: u32, playing: &Playing) -> Option<Self> {
+        let mode = playing.settings.game_mode;
+
+        if mode.metric() == Metric::Time && playing.game_over != Some(GameOverReason::GoalReached) {
+            return None;
+        }
+
+        Some(Entry {
+            name: name.to_string(),
+            date,
+            seed: playing.seed,
+            score: playing.score.num(),
+            rows: playing.rows_removed.num(),
+            pieces: playing.pieces_dropped.num(),
+            frames: playing.time_elapsed.num(),
+        })
+    }
+
+    // true when self should be listed above other
+    fn beats(&self, other: &Entry, metric: Metric) -> bool {
+        match metric {
+            Metric::Score => self.score > other.score,
+            Metric::Time => self.frames < other.frames,
+            Metric::Lines => self.rows > other.rows,
+        }
+    }
+
+    fn metric(&self, metric: Metric) -> String {
+        match metric {
+            Metric::Score => format!("{}", self.score),
+            Metric::Time => format!(
+                "{}:{:02}.{:02}",
+                self.frames / 3600,
+                self.frames / 60 % 60,
+                self.frames % 60 * 100 / 60
+            ),
+            Metric::Lines => format!("{}", self.rows),
+        }
+    }
+
+    // month and day of the civil date
+    fn day(&self) -> String {
+        let z = self.date as i64 + 719_468;
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+
+        format!("{:02}/{:02}", month, day)
+    }
+}
+
+pub trait Storage: Debug {
+    fn load(&self, mode: GameMode) -> Vec<Entry>;
+    fn store(&mut self, mode: GameMode, entries: &[Entry]);
+    fn box_clone(&self) -> Box<dyn Storage>;
+}
+
+impl Clone for Box<dyn Storage> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+// backend for tests
+#[cfg(test)]
+#[derive(Clone, Debug)]
+pub struct Memory {
+    tables: HashMap<GameMode, Vec<Entry>>,
+}
+
+#[cfg(test)]
+impl Memory {
+    pub fn new() -> Self {
+        Memory {
+            tables: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Storage for Memory {
+    fn load(&self, mode: GameMode) -> Vec<Entry> {
+        self.tables.get(&mode).cloned().unwrap_or_default()
+    }
+
+    fn store(&mut self, mode: GameMode, entries: &[Entry]) {
+        self.tables.insert(mode, entries.to_vec());
+    }
+
+    fn box_clone(&self) -> Box<dyn Storage> {
+        Box::new(self.clone())
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = localStorage, js_name = getItem)]
+    fn get_item(key: &str) -> Result<Option<String>, JsValue>;
+    #[wasm_bindgen(catch, js_namespace = localStorage, js_name = setItem)]
+    fn set_item(key: &str, value: &str) -> Result<(), JsValue>;
+}
+
+// keeps a base64 table per mode in the page's localStorage, unreadable or
+// unavailable storage behaves as an empty table
+#[derive(Clone, Debug)]
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn key(mode: GameMode) -> String {
+        format!("tetrust-scores-{}", mode).to_lowercase()
+    }
+}
+
+impl Storage for LocalStorage {
+    fn load(&self, mode: GameMode) -> Vec<Entry> {
+        get_item(&LocalStorage::key(mode))
+            .ok()
+            .and_then(|code| code)
+            .and_then(|code| codec::from_base64(&code).ok())
+            .and_then(|bytes| decode(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&mut self, mode: GameMode, entries: &[Entry]) {
+        let _ = set_item(
+            &LocalStorage::key(mode),
+            &codec::to_base64(&encode(entries)),
+        );
+    }
+
+    fn box_clone(&self) -> Box<dyn Storage> {
+        Box::new(self.clone())
+    }
+}
+
+const MAGIC: &[u8; 4] = b"TETH";
+const VERSION: u8 = 1;
+
+fn encode(entries: &[Entry]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+
+    bytes.push(VERSION);
+    put_varint(&mut bytes, entries.len() as u32);
+    for entry in entries {
+        put_string(&mut bytes, &entry.name);
+        put_varint(&mut bytes, entry.date);
+        put_varint(&mut bytes, entry.seed);
+        put_varint(&mut bytes, entry.score);
+        put_varint(&mut bytes, entry.rows);
+        put_varint(&mut bytes, entry.pieces);
+        put_varint(&mut bytes, entry.frames);
+    }
+    bytes
+}
+
+fn decode(bytes: &[u8]) -> Result<Vec<Entry>, DecodeError> {
+    let mut reader = Reader::new(bytes, MAGIC, VERSION)?;
+    let mut entries = vec![];
+
+    for _ in 0..reader.varint()?.min(CAPACITY as u32) {
+        entries.push(Entry {
+            name: reader.string()?,
+            date: reader.varint()?,
+            seed: reader.varint()?,
+            score: reader.varint()?,
+            rows: reader.varint()?,
+            pieces: reader.varint()?,
+            frames: reader.varint()?,
+        });
+    }
+
+    reader.finish()?;
+    Ok(entries)
+}
+
+#[derive(Clone, Debug)]
+pub struct HighScores {
+    storage: Box<dyn Storage>,
+}
+
+impl HighScores {
+    pub fn new(storage: Box<dyn Storage>) -> Self {
+        HighScores { storage }
+    }
+
+    pub fn table(&self, mode: GameMode) -> Vec<Entry> {
+        self.storage.load(mode)
+    }
+
+    // returns the rank the entry placed at, ties go to the older entry
+    pub fn submit(&mut self, mode: GameMode, entry: Entry) -> Option<usize> {
+        let metric = mode.metric();
+        let mut entries = self.table(mode);
+        let rank = entries
+            .iter()
+            .position(|other| entry.beats(other, metric))
+            .unwrap_or(entries.len());
+
+        if rank >= CAPACITY {
+            return None;
+        }
+
+        entries.insert(rank, entry);
+        entries.truncate(CAPACITY);
+        self.storage.store(mode, &entries);
+        Some(rank)
+    }
+
+    // names an entry after it placed, the player types it in once they see
+    // the result
+    pub fn rename(&mut self, mode: GameMode, rank: usize, name: &str) {
+        let mut entries = self.table(mode);
+
+        if let Some(entry) = entries.get_mut(rank) {
+            entry.name = name.to_string();
+            self.storage.store(mode, &entries);
+        }
+    }
+
+    pub fn draw(&self, grid: &mut Grid, mode: GameMode) {
+        let metric = mode.metric();
+
+        grid.draw_rect(12, 1, 24, GRID_HEIGHT - 2, Tile::Space);
+        grid.draw_string(Pos(18, 3), "High Scores");
+        grid.draw_string(Pos(15, 5), &format!("< {} >", mode));
+
+        let entries = self.table(mode);
+
+        if entries.is_empty() {
+            grid.draw_string(Pos(15, 8), "No scores yet");
+        }
+
+        for (i, entry) in entries.iter().enumerate() {
+            let name: String = entry.name.chars().take(6).collect();
+
+            grid.draw_string(
+                Pos(13, 8 + i as u32),
+                &format!(
+                    "{:>2} {:<6} {:>7} {}",
+                    i + 1,
+                    name,
+                    entry.metric(metric),
+                    entry.day()
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32, frames: u32) -> Entry {
+        Entry {
+            name: name.to_string(),
+            date: 20000,
+            seed: 0,
+            score,
+            rows: score / 100,
+            pieces: 0,
+            frames,
+        }
+    }
+
+    #[test]
+    fn ranks_by_mode_metric() {
+        let mut high_scores = HighScores::new(Box::new(Memory::new()));
+
+        assert_eq!(
+            high_scores.submit(GameMode::Marathon, entry("A", 500, 900)),
+            Some(0)
+        );
+        assert_eq!(
+            high_scores.submit(GameMode::Marathon, entry("B", 900, 600)),
+            Some(0)
+        );
+        assert_eq!(
+            high_scores.submit(GameMode::Marathon, entry("C", 500, 300)),
+            Some(2)
+        );
+        assert_eq!(
+            high_scores.submit(GameMode::Sprint, entry("A", 500, 900)),
+            Some(0)
+        );
+        assert_eq!(
+            high_scores.submit(GameMode::Sprint, entry("B", 900, 600)),
+            Some(0)
+        );
+
+        let names = |mode| -> Vec<String> {
+            high_scores
+                .table(mode)
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect()
+        };
+
+        assert_eq!(names(GameMode::Marathon), vec!["B", "A", "C"]);
+        assert_eq!(names(GameMode::Sprint), vec!["B", "A"]);
+        assert!(names(GameMode::Ultra).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_best_results() {
+        let mut high_scores = HighScores::new(Box::new(Memory::new()));
+
+        for score in 0..CAPACITY as u32 {
+            high_scores.submit(GameMode::Ultra, entry("A", 100 + score, 0));
+        }
+
+        assert_eq!(high_scores.submit(GameMode::Ultra, entry("B", 50, 0)), None);
+        assert_eq!(
+            high_scores.submit(GameMode::Ultra, entry("C", 105, 0)),
+            Some(5)
+        );
+
+        let table = high_scores.table(GameMode::Ultra);
+
+        assert_eq!(table.len(), CAPACITY);
+        assert_eq!(table.last().unwrap().score, 101);
+    }
+
+    #[test]
+    fn renames_placed_entries() {
+        let mut high_scores = HighScores::new(Box::new(Memory::new()));
+
+        high_scores.submit(GameMode::Marathon, entry("A", 500, 0));
+        high_scores.submit(GameMode::Marathon, entry("B", 900, 0));
+        high_scores.rename(GameMode::Marathon, 1, "ALICE");
+        high_scores.rename(GameMode::Marathon, CAPACITY, "BOB");
+
+        let table = high_scores.table(GameMode::Marathon);
+
+        assert_eq!(table[0].name, "B");
+        assert_eq!(table[1].name, "ALICE");
+        assert_eq!(table[1].score, 500);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn encoded_tables_round_trip() {
+        let entries = vec![entry("PLAYER", 1200, 3600), entry("", 0, 0)];
+
+        assert_eq!(decode(&encode(&entries)), Ok(entries));
+        assert_eq!(entry("A", 0, 0).day(), "10/04");
+        assert_eq!(entry("A", 0, 5025).metric(Metric::Time), "1:23.75");
+    }
+}
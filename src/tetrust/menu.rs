@@ -1,5 +1,125 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Menu {
+    Continue,
     NewGame,
+    GameMode,
+    Seed,
+    HighScores,
     Options,
+    Ghost,
+    Stats,
+    FinesseRetry,
+    LockDelay,
+    LockReset,
+    NextQueue,
+    Theme,
+    Randomizer,
+    Das,
+    Arr,
+    SoftDrop,
+    DasCut,
+    Opposing,
+    Back,
+}
+
+impl Menu {
+    pub fn next(&self) -> Self {
+        match self {
+            Menu::Continue => Menu::NewGame,
+            Menu::NewGame => Menu::GameMode,
+            Menu::GameMode => Menu::Seed,
+            Menu::Seed => Menu::HighScores,
+            Menu::HighScores => Menu::Options,
+            Menu::Options => Menu::Continue,
+            Menu::Ghost => Menu::Stats,
+            Menu::Stats => Menu::FinesseRetry,
+            Menu::FinesseRetry => Menu::LockDelay,
+            Menu::LockDelay => Menu::LockReset,
+            Menu::LockReset => Menu::NextQueue,
+            Menu::NextQueue => Menu::Theme,
+            Menu::Theme => Menu::Randomizer,
+            Menu::Randomizer => Menu::Das,
+            Menu::Das => Menu::Arr,
+            Menu::Arr => Menu::SoftDrop,
+            Menu::SoftDrop => Menu::DasCut,
+            Menu::DasCut => Menu::Opposing,
+            Menu::Opposing => Menu::Back,
+            Menu::Back => Menu::Ghost,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self {
+            Menu::Continue => Menu::Options,
+            Menu::NewGame => Menu::Continue,
+            Menu::GameMode => Menu::NewGame,
+            Menu::Seed => Menu::GameMode,
+            Menu::HighScores => Menu::Seed,
+            Menu::Options => Menu::HighScores,
+            Menu::Ghost => Menu::Back,
+            Menu::Stats => Menu::Ghost,
+            Menu::FinesseRetry => Menu::Stats,
+            Menu::LockDelay => Menu::FinesseRetry,
+            Menu::LockReset => Menu::LockDelay,
+            Menu::NextQueue => Menu::LockReset,
+            Menu::Theme => Menu::NextQueue,
+            Menu::Randomizer => Menu::Theme,
+            Menu::Das => Menu::Randomizer,
+            Menu::Arr => Menu::Das,
+            Menu::SoftDrop => Menu::Arr,
+            Menu::DasCut => Menu::SoftDrop,
+            Menu::Opposing => Menu::DasCut,
+            Menu::Back => Menu::Opposing,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseMenu {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseMenu {
+    pub fn next(self) -> Self {
+        match self {
+            PauseMenu::Resume => PauseMenu::Restart,
+            PauseMenu::Restart => PauseMenu::Quit,
+            PauseMenu::Quit => PauseMenu::Resume,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            PauseMenu::Resume => PauseMenu::Quit,
+            PauseMenu::Restart => PauseMenu::Resume,
+            PauseMenu::Quit => PauseMenu::Restart,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultsMenu {
+    Retry,
+    Replay,
+    MainMenu,
+}
+
+impl ResultsMenu {
+    pub fn next(self) -> Self {
+        match self {
+            ResultsMenu::Retry => ResultsMenu::Replay,
+            ResultsMenu::Replay => ResultsMenu::MainMenu,
+            ResultsMenu::MainMenu => ResultsMenu::Retry,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            ResultsMenu::Retry => ResultsMenu::MainMenu,
+            ResultsMenu::Replay => ResultsMenu::Retry,
+            ResultsMenu::MainMenu => ResultsMenu::Replay,
+        }
+    }
 }
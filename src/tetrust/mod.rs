@@ -1,11 +1,25 @@
+mod clear;
+mod codec;
 mod color;
 mod controls;
+mod finesse;
 mod game;
+mod game_mode;
+mod high_scores;
 mod menu;
 mod piece;
 mod playing;
+mod randomizer;
 mod regulator;
+mod replay;
+mod replay_file;
+mod rng;
+mod scoring;
+mod settings;
 mod shape;
 mod stack;
+mod theme;
 
+pub use self::playing::Playing;
 pub use self::regulator::Regulator;
+pub use self::replay_file::{from_base64, to_base64};
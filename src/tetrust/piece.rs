@@ -1,29 +1,81 @@
 use grid::{Pos, Tile};
 use tetrust::color::Color;
 use tetrust::shape::Shape;
+use tetrust::stack::{BUFFER_ROWS, STACK_ROWS};
+use tetrust::theme::Theme;
 
 pub use grid::Grid;
 
+// SRS wall kick tests indexed by starting orientation then clockwise (0) or
+// counterclockwise (1). Rows grow downward so y is negated from the SRS tables.
+const JLSTZ_KICKS: [[[(i32, i32); 5]; 2]; 4] = [
+    [
+        [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+        [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    ],
+    [
+        [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+        [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    ],
+    [
+        [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+        [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    ],
+    [
+        [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+        [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    ],
+];
+
+const I_KICKS: [[[(i32, i32); 5]; 2]; 4] = [
+    [
+        [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],
+        [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+    ],
+    [
+        [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+        [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],
+    ],
+    [
+        [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],
+        [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+    ],
+    [
+        [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+        [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)],
+    ],
+];
+
+const O_KICKS: [(i32, i32); 1] = [(0, 0)];
+
+// pieces spawn in the two buffer rows just above the visible playfield
+const SPAWN_ROW: i32 = BUFFER_ROWS as i32 - 2;
+
 #[derive(Clone, Debug)]
 pub struct Piece {
+    pub shape: Shape,
     pub offset: (i32, i32),
     pub orientations: Vec<Vec<Vec<bool>>>,
     pub orientation: usize,
+    // index of the kick test that placed the piece, None unless the last
+    // successful movement was a rotation
+    pub kick: Option<usize>,
     pub color: Color,
 }
 
 impl Piece {
     pub fn new(shape: Shape, color: Color) -> Piece {
         Piece {
+            shape,
             offset: {
                 match shape {
-                    Shape::O => (4, 0),
-                    Shape::I => (3, 0),
-                    Shape::T => (4, 0),
-                    Shape::S => (4, 0),
-                    Shape::Z => (4, 0),
-                    Shape::L => (4, 0),
-                    Shape::J => (4, 0),
+                    Shape::O => (4, SPAWN_ROW),
+                    Shape::I => (3, SPAWN_ROW),
+                    Shape::T => (3, SPAWN_ROW),
+                    Shape::S => (3, SPAWN_ROW),
+                    Shape::Z => (3, SPAWN_ROW),
+                    Shape::L => (3, SPAWN_ROW),
+                    Shape::J => (3, SPAWN_ROW),
                 }
             },
             orientations: {
@@ -38,6 +90,7 @@ impl Piece {
                 }
             },
             orientation: 0,
+            kick: None,
             color,
         }
     }
@@ -45,6 +98,7 @@ impl Piece {
     pub fn move_down(&self) -> Self {
         Piece {
             offset: (self.offset.0, self.offset.1 + 1),
+            kick: None,
             ..self.clone()
         }
     }
@@ -52,6 +106,7 @@ impl Piece {
     pub fn move_left(&self) -> Self {
         Piece {
             offset: (self.offset.0 - 1, self.offset.1),
+            kick: None,
             ..self.clone()
         }
     }
@@ -59,6 +114,7 @@ impl Piece {
     pub fn move_right(&self) -> Self {
         Piece {
             offset: (self.offset.0 + 1, self.offset.1),
+            kick: None,
             ..self.clone()
         }
     }
@@ -81,7 +137,36 @@ impl Piece {
         }
     }
 
+    pub fn kicks(&self, clockwise: bool) -> &'static [(i32, i32)] {
+        let direction = if clockwise { 0 } else { 1 };
+
+        match self.shape {
+            Shape::O => &O_KICKS,
+            Shape::I => &I_KICKS[self.orientation][direction],
+            _ => &JLSTZ_KICKS[self.orientation][direction],
+        }
+    }
+
+    pub fn kick(&self, index: usize, translation: (i32, i32)) -> Self {
+        Piece {
+            offset: (self.offset.0 + translation.0, self.offset.1 + translation.1),
+            kick: Some(index),
+            ..self.clone()
+        }
+    }
+
     pub fn inside_bounds(&self) -> bool {
+        self.blocks().into_iter().all(inside_bounds)
+    }
+
+    // true when no block reaches the visible playfield
+    pub fn above_playfield(&self) -> bool {
+        self.blocks()
+            .into_iter()
+            .all(|(_, y)| y < BUFFER_ROWS as i32)
+    }
+
+    pub fn blocks(&self) -> Vec<(i32, i32)> {
         let orientation = &self.orientations[self.orientation];
         let width = orientation.len();
 
@@ -95,37 +180,36 @@ impl Piece {
                     }
                 })
             })
-            .all(|x| inside_bounds(x))
+            .collect()
     }
 }
 
 impl Grid {
-    pub fn draw_piece(&mut self, piece: &Piece, x: u32, y: u32) {
-        let orientation = &piece.orientations[piece.orientation];
-        let width = orientation.len();
+    // draws a piece in the playfield at (x, y), clipping the buffer rows
+    pub fn draw_piece(&mut self, piece: &Piece, x: u32, y: u32, theme: Theme) {
+        self.draw_piece_tiles(piece, x, y, BUFFER_ROWS as i32, theme.tile(piece.color));
+    }
 
-        for j in 0..width {
-            for i in 0..width {
-                if orientation[j][i] {
-                    self[Pos(
-                        ((x as i32) + piece.offset.0 + (i as i32)) as u32,
-                        ((y as i32) + piece.offset.1 + (j as i32)) as u32,
-                    )] = match piece.color {
-                        Color::Blue => Tile::BlueSolid,
-                        Color::BlueWhite => Tile::BlueWhite,
-                        Color::Cyan => Tile::CyanSolid,
-                        Color::CyanWhite => Tile::CyanWhite,
-                        Color::Grey => Tile::GreySolid,
-                        Color::GreyWhite => Tile::GreyWhite,
-                    };
-                }
+    pub fn draw_ghost(&mut self, piece: &Piece, x: u32, y: u32) {
+        self.draw_piece_tiles(piece, x, y, BUFFER_ROWS as i32, Tile::Ghost);
+    }
+
+    // draws a piece outside the playfield ignoring its vertical offset
+    pub fn draw_preview(&mut self, piece: &Piece, x: u32, y: u32, theme: Theme) {
+        self.draw_piece_tiles(piece, x, y, piece.offset.1, theme.tile(piece.color));
+    }
+
+    fn draw_piece_tiles(&mut self, piece: &Piece, x: u32, y: u32, top: i32, tile: Tile) {
+        for (i, j) in piece.blocks() {
+            if j >= top {
+                self[Pos((x as i32 + i) as u32, (y as i32 + j - top) as u32)] = tile;
             }
         }
     }
 }
 
 fn o_template() -> Vec<Vec<Vec<bool>>> {
-    vec![vec![vec![true, true], vec![true, true]]]
+    vec![vec![vec![true, true], vec![true, true]]; 4]
 }
 
 fn i_template() -> Vec<Vec<Vec<bool>>> {
@@ -142,6 +226,18 @@ fn i_template() -> Vec<Vec<Vec<bool>>> {
             vec![false, false, true, false],
             vec![false, false, true, false],
         ],
+        vec![
+            vec![false, false, false, false],
+            vec![false, false, false, false],
+            vec![true, true, true, true],
+            vec![false, false, false, false],
+        ],
+        vec![
+            vec![false, true, false, false],
+            vec![false, true, false, false],
+            vec![false, true, false, false],
+            vec![false, true, false, false],
+        ],
     ]
 }
 
@@ -177,6 +273,16 @@ fn s_template() -> Vec<Vec<Vec<bool>>> {
             vec![true, true, false],
             vec![false, false, false],
         ],
+        vec![
+            vec![false, true, false],
+            vec![false, true, true],
+            vec![false, false, true],
+        ],
+        vec![
+            vec![false, false, false],
+            vec![false, true, true],
+            vec![true, true, false],
+        ],
         vec![
             vec![true, false, false],
             vec![true, true, false],
@@ -192,6 +298,16 @@ fn z_template() -> Vec<Vec<Vec<bool>>> {
             vec![false, true, true],
             vec![false, false, false],
         ],
+        vec![
+            vec![false, false, true],
+            vec![false, true, true],
+            vec![false, true, false],
+        ],
+        vec![
+            vec![false, false, false],
+            vec![true, true, false],
+            vec![false, true, true],
+        ],
         vec![
             vec![false, true, false],
             vec![true, true, false],
@@ -203,24 +319,24 @@ fn z_template() -> Vec<Vec<Vec<bool>>> {
 fn l_template() -> Vec<Vec<Vec<bool>>> {
     vec![
         vec![
-            vec![false, false, false],
+            vec![false, false, true],
             vec![true, true, true],
-            vec![true, false, false],
+            vec![false, false, false],
         ],
         vec![
-            vec![true, true, false],
             vec![false, true, false],
             vec![false, true, false],
+            vec![false, true, true],
         ],
         vec![
-            vec![false, false, true],
-            vec![true, true, true],
             vec![false, false, false],
+            vec![true, true, true],
+            vec![true, false, false],
         ],
         vec![
+            vec![true, true, false],
             vec![false, true, false],
             vec![false, true, false],
-            vec![false, true, true],
         ],
     ]
 }
@@ -251,5 +367,5 @@ fn j_template() -> Vec<Vec<Vec<bool>>> {
 }
 
 fn inside_bounds(s: (i32, i32)) -> bool {
-    s.0 >= 0 && s.0 <= 9 && s.1 >= 0 && s.1 <= 19
+    s.0 >= 0 && s.0 <= 9 && s.1 >= 0 && s.1 < STACK_ROWS as i32
 }
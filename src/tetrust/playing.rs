@@ -1,10 +1,40 @@
-use rand::rngs::SmallRng;
+use std::collections::VecDeque;
 use std::fmt::{Display, Formatter, Result};
+use std::ops::RangeInclusive;
+use tetrust::clear::{Clear, Spin};
+use tetrust::codec::{
+    put_color, put_int, put_long, put_settings, put_shape, put_string, put_varint, DecodeError,
+    Reader,
+};
 use tetrust::color::Color;
-use tetrust::controls::Controls;
+use tetrust::controls::{Button, Controls};
+use tetrust::finesse::{Judgement, Move};
+use tetrust::game_mode::GameMode;
 use tetrust::piece::Piece;
+use tetrust::randomizer::{self, Randomizer};
+use tetrust::rng::Pcg32;
+use tetrust::settings::{Handling, LockReset, Settings, MOVE_RESETS};
 use tetrust::shape::Shape;
-use tetrust::stack::Stack;
+use tetrust::stack::{Stack, STACK_ROWS};
+use tetrust::theme::Theme;
+
+// cells fallen per frame in 1/65536ths, following the guideline curve of
+// (0.8 - (level - 1) * 0.007) ^ (level - 1) seconds per row until 20G
+const GRAVITY: [u32; 18] = [
+    1092, 1377, 1768, 2311, 3075, 4169, 5759, 8107, 11634, 17026, 25416, 38709, 60169, 95483,
+    154742, 256187, 433425, 749597,
+];
+
+const MAX_GRAVITY: u32 = 20 << 16;
+
+// counters restored from a save stay far from overflowing
+const MAX_COUNT: u32 = 1 << 28;
+
+// heights a restored piece may be found at
+const ROWS: RangeInclusive<i32> = -2 * STACK_ROWS as i32..=2 * STACK_ROWS as i32;
+
+const BANNER_WIDTH: usize = 11;
+const BANNER_FRAMES: u32 = 120;
 
 #[derive(Clone, Copy, Debug)]
 pub struct Level {
@@ -20,6 +50,12 @@ impl Level {
             num: self.num + rows_removed.num / 10,
         }
     }
+    fn gravity(self) -> u32 {
+        GRAVITY
+            .get(self.num as usize)
+            .cloned()
+            .unwrap_or(MAX_GRAVITY)
+    }
 }
 
 impl Display for Level {
@@ -28,7 +64,7 @@ impl Display for Level {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Score {
     num: u32,
 }
@@ -39,9 +75,12 @@ impl Score {
     }
     fn add(self, points: u32) -> Self {
         Score {
-            num: self.num + points,
+            num: self.num.saturating_add(points),
         }
     }
+    pub fn num(self) -> u32 {
+        self.num
+    }
 }
 
 impl Display for Score {
@@ -62,6 +101,9 @@ impl TimeElapsed {
     fn inc(self) -> Self {
         TimeElapsed { num: self.num + 1 }
     }
+    pub fn num(self) -> u32 {
+        self.num
+    }
 }
 
 impl Display for TimeElapsed {
@@ -70,7 +112,7 @@ impl Display for TimeElapsed {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct RowsRemoved {
     num: u32,
 }
@@ -84,6 +126,9 @@ impl RowsRemoved {
             num: self.num + num,
         }
     }
+    pub fn num(self) -> u32 {
+        self.num
+    }
 }
 
 impl Display for RowsRemoved {
@@ -104,6 +149,9 @@ impl PiecesDropped {
     fn inc(self) -> Self {
         PiecesDropped { num: self.num + 1 }
     }
+    pub fn num(self) -> u32 {
+        self.num
+    }
 }
 
 impl Display for PiecesDropped {
@@ -112,197 +160,1560 @@ impl Display for PiecesDropped {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct Combo {
+    num: Option<u32>,
+}
+
+impl Combo {
+    fn new() -> Self {
+        Combo { num: None }
+    }
+    fn iterate(self, num_rows: u32) -> Self {
+        Combo {
+            num: if num_rows > 0 {
+                Some(self.num.map_or(0, |num| num + 1))
+            } else {
+                None
+            },
+        }
+    }
+    fn count(self) -> u32 {
+        self.num.unwrap_or(0)
+    }
+    // guideline garbage for the chain, one more line every other clear
+    fn attack(self) -> u32 {
+        match self.count() {
+            0 => 0,
+            1 | 2 => 1,
+            3 | 4 => 2,
+            5 | 6 => 3,
+            7..=9 => 4,
+            _ => 5,
+        }
+    }
+}
+
+impl Display for Combo {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "CMB:{}", self.count())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PerfectClears {
+    num: u32,
+}
+
+impl PerfectClears {
+    fn new() -> Self {
+        PerfectClears { num: 0 }
+    }
+    fn inc(self) -> Self {
+        PerfectClears { num: self.num + 1 }
+    }
+}
+
+impl Display for PerfectClears {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "PC:{}", self.num)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Attack {
+    num: u32,
+}
+
+impl Attack {
+    fn new() -> Self {
+        Attack { num: 0 }
+    }
+    fn add(self, num: u32) -> Self {
+        Attack {
+            num: self.num + num,
+        }
+    }
+}
+
+impl Display for Attack {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "ATK:{}", self.num)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keys {
+    num: u32,
+}
+
+impl Keys {
+    fn new() -> Self {
+        Keys { num: 0 }
+    }
+    fn add(self, num: u32) -> Self {
+        Keys {
+            num: self.num + num,
+        }
+    }
+}
+
+// pieces placed with more presses than the shortest way there
+#[derive(Clone, Copy, Debug)]
+pub struct FinesseFaults {
+    num: u32,
+}
+
+impl FinesseFaults {
+    fn new() -> Self {
+        FinesseFaults { num: 0 }
+    }
+    fn inc(self) -> Self {
+        FinesseFaults { num: self.num + 1 }
+    }
+}
+
+impl Display for FinesseFaults {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "FIN:{}", self.num)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Banner {
+    pub lines: Vec<String>,
+    timer: u32,
+}
+
+impl Banner {
+    fn new(text: &[String]) -> Option<Self> {
+        let mut lines: Vec<String> = vec![];
+
+        for word in text.iter().flat_map(|line| line.split(' ')) {
+            match lines.last_mut() {
+                Some(line) if !line.is_empty() && line.len() + 1 + word.len() <= BANNER_WIDTH => {
+                    line.push(' ');
+                    line.push_str(word);
+                }
+                _ => lines.push(word.to_string()),
+            }
+        }
+
+        lines.retain(|line| !line.is_empty());
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(Banner {
+                lines,
+                timer: BANNER_FRAMES,
+            })
+        }
+    }
+
+    fn iterate(&self) -> Option<Self> {
+        if self.timer == 0 {
+            None
+        } else {
+            Some(Banner {
+                timer: self.timer - 1,
+                ..self.clone()
+            })
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LockDelay {
+    timer: u32,
+    resets: u32,
+    lowest: i32,
+}
+
+impl LockDelay {
+    fn new(piece: &Piece, settings: &Settings) -> Self {
+        LockDelay {
+            timer: settings.lock_delay,
+            resets: 0,
+            lowest: piece.offset.1,
+        }
+    }
+
+    // returns None once the piece should lock
+    fn iterate(
+        self,
+        piece: &Piece,
+        moved: bool,
+        grounded: bool,
+        settings: &Settings,
+    ) -> Option<Self> {
+        if piece.offset.1 > self.lowest {
+            return Some(LockDelay::new(piece, settings));
+        }
+
+        let lock_delay = match settings.lock_reset {
+            LockReset::Infinity if moved => LockDelay {
+                timer: settings.lock_delay,
+                ..self
+            },
+            LockReset::Move(limit) if moved && self.resets < limit => LockDelay {
+                timer: settings.lock_delay,
+                resets: self.resets + 1,
+                ..self
+            },
+            LockReset::Move(limit) if grounded && self.resets >= limit => return None,
+            _ => self,
+        };
+
+        if !grounded {
+            Some(lock_delay)
+        } else if lock_delay.timer == 0 {
+            None
+        } else {
+            Some(LockDelay {
+                timer: lock_delay.timer - 1,
+                ..lock_delay
+            })
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameOverReason {
+    BlockOut,
+    LockOut,
+    GoalReached,
+    TimeExpired,
+}
+
+impl Display for GameOverReason {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            GameOverReason::BlockOut => write!(f, "BLOCK OUT"),
+            GameOverReason::LockOut => write!(f, "LOCK OUT"),
+            GameOverReason::GoalReached => write!(f, "COMPLETE"),
+            GameOverReason::TimeExpired => write!(f, "TIME UP"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Mode {
-    DroppingPiece(Piece),
-    DissolvingRows(u32),
-    //    Paused
+    DroppingPiece(Piece, LockDelay),
+    DissolvingRows(u32, Spin),
 }
 
 #[derive(Clone, Debug)]
 pub struct Playing {
     pub stack: Stack,
-    pub shape: Shape,
-    pub color: Color,
+    pub next: VecDeque<Piece>,
     pub mode: Mode,
-    pub rng: SmallRng,
+    pub hold: Option<Piece>,
+    pub can_hold: bool,
+    pub seed: u32,
+    pub rng: Pcg32,
+    pub randomizer: Box<dyn Randomizer>,
     pub base_level: Level,
     pub rows_removed: RowsRemoved,
     pub score: Score,
     pub pieces_dropped: PiecesDropped,
     pub time_elapsed: TimeElapsed,
-    pub game_over: bool,
+    pub combo: Combo,
+    pub perfect_clears: PerfectClears,
+    pub attack: Attack,
+    pub keys: Keys,
+    // moves pressed for the piece in play, None once it was soft or sonic
+    // dropped and no longer has a fair shortest path
+    pub piece_inputs: Option<u32>,
+    pub finesse_faults: FinesseFaults,
+    // the last piece locked without soft dropping
+    pub judgement: Option<Judgement>,
+    // buttons held through a finesse retry, ignored until they are released
+    // so the retried piece starts without a das charge
+    pub ignored: Vec<Button>,
+    pub back_to_back: bool,
+    pub banner: Option<Banner>,
+    pub gravity: u32,
+    pub das_cut: u32,
+    pub game_over: Option<GameOverReason>,
+    pub settings: Settings,
 }
 
 use tetrust::game::input_trigger;
 
+fn deal(randomizer: &mut Box<dyn Randomizer>, rng: &mut Pcg32, theme: Theme) -> Piece {
+    let shape = randomizer.next(rng);
+
+    Piece::new(shape, theme.color(shape, Color::rand(rng)))
+}
+
 impl Playing {
-    pub fn new(base_level: Level, rng: &SmallRng) -> Self {
-        let mut rng = rng.clone();
+    pub fn new(
+        base_level: Level,
+        seed: u32,
+        randomizer: Box<dyn Randomizer>,
+        settings: &Settings,
+    ) -> Self {
+        let mut rng = Pcg32::new(seed);
+        let mut randomizer = randomizer;
+        let piece = deal(&mut randomizer, &mut rng, settings.theme);
+        let next = (0..settings.next_queue)
+            .map(|_| deal(&mut randomizer, &mut rng, settings.theme))
+            .collect();
+        let stack = Stack::new();
+        let piece = piece.enter(&stack);
 
         Playing {
-            game_over: false,
-            stack: Stack::new(),
-            shape: Shape::rand(&mut rng),
-            color: Color::rand(&mut rng),
-            mode: Mode::DroppingPiece(Piece::new(Shape::rand(&mut rng), Color::rand(&mut rng))),
+            game_over: None,
+            stack,
+            next,
+            mode: Mode::DroppingPiece(piece.clone(), LockDelay::new(&piece, settings)),
+            hold: None,
+            can_hold: true,
             base_level: base_level,
             score: Score::new(),
             time_elapsed: TimeElapsed::new(),
+            combo: Combo::new(),
+            perfect_clears: PerfectClears::new(),
+            attack: Attack::new(),
+            keys: Keys::new(),
+            piece_inputs: Some(0),
+            finesse_faults: FinesseFaults::new(),
+            judgement: None,
+            ignored: vec![],
+            back_to_back: false,
+            banner: None,
+            gravity: 0,
+            das_cut: settings.handling.das_cut,
+            seed,
             rng: rng,
+            randomizer,
             pieces_dropped: PiecesDropped::new(),
             rows_removed: RowsRemoved::new(),
+            settings: settings.clone(),
         }
     }
 
     fn increment_time(&self) -> Self {
         Playing {
             time_elapsed: self.time_elapsed.inc(),
+            banner: self.banner.as_ref().and_then(|banner| banner.iterate()),
+            ..self.clone()
+        }
+    }
+
+    fn spawn_next(&self) -> Self {
+        let mut rng = self.rng.clone();
+        let mut randomizer = self.randomizer.clone();
+        let mut next = self.next.clone();
+        next.push_back(deal(&mut randomizer, &mut rng, self.settings.theme));
+        let piece = next.pop_front().expect("next queue is never empty");
+
+        Playing {
+            next,
+            rng,
+            randomizer,
+            ..self.clone()
+        }
+        .spawn_piece(piece)
+    }
+
+    // block out when the spawn position is obstructed
+    fn spawn_piece(&self, piece: Piece) -> Self {
+        if !piece.fits(&self.stack) {
+            return Playing {
+                game_over: Some(GameOverReason::BlockOut),
+                ..self.clone()
+            };
+        }
+
+        let piece = piece.enter(&self.stack);
+
+        Playing {
+            das_cut: self.settings.handling.das_cut,
+            piece_inputs: Some(0),
+            mode: Mode::DroppingPiece(piece.clone(), LockDelay::new(&piece, &self.settings)),
             ..self.clone()
         }
     }
 
+    fn hold_piece(&self, piece: &Piece) -> Self {
+        let playing = Playing {
+            hold: Some(Piece::new(piece.shape, piece.color)),
+            can_hold: false,
+            gravity: 0,
+            ..self.clone()
+        };
+
+        match &self.hold {
+            Some(held) => playing.spawn_piece(Piece::new(held.shape, held.color)),
+            None => playing.spawn_next(),
+        }
+    }
+
+    // lock out when the whole piece locks above the visible playfield, which
+    // also covers any piece reaching the top of the buffer since pieces are
+    // at most four rows tall, finesse practice flags a fault straight away
+    // and can take the piece back
+    fn lock_piece(&self, piece: &Piece, score: Score, controls: &Controls) -> Self {
+        let judgement = self
+            .piece_inputs
+            .and_then(|inputs| Judgement::new(inputs, piece));
+        let fault = judgement.as_ref().is_some_and(Judgement::fault);
+        let practice = self.settings.game_mode == GameMode::Finesse;
+        let playing = Playing {
+            finesse_faults: if fault {
+                self.finesse_faults.inc()
+            } else {
+                self.finesse_faults
+            },
+            banner: if practice && fault {
+                Banner::new(&["FINESSE FAULT".to_string()])
+            } else {
+                self.banner.clone()
+            },
+            judgement,
+            gravity: 0,
+            ..self.clone()
+        };
+
+        if practice && fault && self.settings.finesse_retry {
+            return Playing {
+                ignored: controls.held(),
+                ..playing
+            }
+            .spawn_piece(Piece::new(piece.shape, piece.color));
+        }
+
+        let stack = self.stack.add_piece(piece);
+
+        Playing {
+            game_over: if piece.above_playfield() {
+                Some(GameOverReason::LockOut)
+            } else {
+                None
+            },
+            mode: Mode::DissolvingRows(
+                if stack.can_remove_lines() { 30 } else { 15 },
+                piece.spin(&self.stack),
+            ),
+            stack,
+            score,
+            pieces_dropped: self.pieces_dropped.inc(),
+            ..playing
+        }
+    }
+
     fn dissolve_rows(&self, controls: &Controls) -> Self {
         match &self.mode {
-            Mode::DroppingPiece(piece) => {
-                match piece
-                    .input_move_left(&controls, &self.stack)
-                    .input_move_right(&controls, &self.stack)
-                    .input_rotate_left(&controls, &self.stack)
-                    .input_rotate_right(&controls, &self.stack)
-                    .input_move_down(&controls, &self.stack)
-                    .and_then(|piece| {
-                        piece.automatically_move_down(
-                            self.time_elapsed,
-                            self.base_level,
-                            self.rows_removed,
-                            &self.stack,
-                        )
-                    }) {
-                    Ok(piece) => Playing {
-                        mode: Mode::DroppingPiece(piece),
-                        ..self.clone()
-                    },
-                    Err(stack) => Playing {
-                        mode: Mode::DissolvingRows(if stack.can_remove_lines() { 30 } else { 15 }),
-                        stack: stack,
-                        pieces_dropped: self.pieces_dropped.inc(),
-                        ..self.clone()
-                    },
+            Mode::DroppingPiece(piece, _) if self.can_hold && controls.hold == Some(0) => {
+                self.hold_piece(piece)
+            }
+            Mode::DroppingPiece(piece, lock_delay) => {
+                let handling = self.settings.handling;
+                let das_cut = self.das_cut > 0;
+                let moved_piece = piece
+                    .input_move_left(controls, &self.stack, handling, das_cut)
+                    .input_move_right(controls, &self.stack, handling, das_cut)
+                    .input_rotate_left(controls, &self.stack)
+                    .input_rotate_right(controls, &self.stack);
+                let das_cut = if moved_piece.orientation != piece.orientation {
+                    handling.das_cut
+                } else {
+                    self.das_cut.saturating_sub(1)
+                };
+
+                if controls.hard_drop == Some(0) {
+                    let dropped_piece = moved_piece.drop(&self.stack);
+
+                    self.lock_piece(
+                        &dropped_piece,
+                        self.score.add(
+                            self.settings
+                                .game_mode
+                                .scoring_rules()
+                                .hard_drop(moved_piece.rows_to(&dropped_piece)),
+                        ),
+                        controls,
+                    )
+                } else {
+                    let soft_dropping = controls.down.is_some();
+                    let soft_dropped_piece = moved_piece
+                        .input_sonic_drop(controls, &self.stack)
+                        .input_soft_drop(controls, &self.stack, handling);
+                    let level_gravity = self.base_level.adjusted_level(self.rows_removed).gravity();
+                    let gravity = self.gravity.saturating_add(if soft_dropping {
+                        level_gravity.saturating_mul(handling.soft_drop)
+                    } else {
+                        level_gravity
+                    });
+                    let fallen_piece = soft_dropped_piece.fall(gravity >> 16, &self.stack);
+                    // rows gravity alone would have pulled the piece down earn
+                    // no soft drop points
+                    let natural_piece =
+                        soft_dropped_piece.fall((self.gravity + level_gravity) >> 16, &self.stack);
+                    let score = self
+                        .score
+                        .add(self.settings.game_mode.scoring_rules().soft_drop(
+                            moved_piece.rows_to(&soft_dropped_piece)
+                                + if soft_dropping {
+                                    natural_piece.rows_to(&fallen_piece)
+                                } else {
+                                    0
+                                },
+                        ));
+                    let grounded = !fallen_piece.move_down().fits(&self.stack);
+
+                    match lock_delay.iterate(
+                        &fallen_piece,
+                        moved_piece.offset != piece.offset
+                            || moved_piece.orientation != piece.orientation,
+                        grounded,
+                        &self.settings,
+                    ) {
+                        Some(lock_delay) => Playing {
+                            mode: Mode::DroppingPiece(fallen_piece, lock_delay),
+                            score,
+                            gravity: if grounded { 0 } else { gravity & 0xffff },
+                            das_cut,
+                            ..self.clone()
+                        },
+                        None => self.lock_piece(&fallen_piece, score, controls),
+                    }
                 }
             }
-            Mode::DissolvingRows(1) => {
-                let mut rng = self.rng.clone();
-                let piece = Piece::new(self.shape, self.color);
+            Mode::DissolvingRows(1, spin) => {
                 let (stack, num_rows) = self.stack.remove_lines();
+                let level = self.base_level.adjusted_level(self.rows_removed);
+                let clear = Clear::new(*spin, num_rows);
+                let combo = self.combo.iterate(num_rows);
+                let back_to_back_bonus = self.back_to_back && clear.difficult();
+                let rules = self.settings.game_mode.scoring_rules();
+                let points = rules.clear(clear, num_rows, level.num);
+                let points = if back_to_back_bonus {
+                    rules.back_to_back(points)
+                } else {
+                    points
+                };
+                let perfect_clear = num_rows > 0 && stack.is_empty();
+                let points = if perfect_clear {
+                    points + rules.perfect_clear(num_rows, back_to_back_bonus, level.num)
+                } else {
+                    points
+                };
+                let mut banner = vec![clear.to_string()];
 
-                Playing {
-                    game_over: stack.overlaps(&piece),
-                    shape: Shape::rand(&mut rng),
-                    color: Color::rand(&mut rng),
-                    rng: rng,
-                    score: self.score.add(
-                        num_rows
-                            * (100 + 10 * self.base_level.adjusted_level(self.rows_removed).num),
-                    ),
+                if perfect_clear {
+                    banner.insert(0, "ALL CLEAR".to_string());
+                }
+                if back_to_back_bonus {
+                    banner.push("B2B".to_string());
+                }
+                if combo.count() > 0 {
+                    banner.push(format!("COMBO x{}", combo.count()));
+                }
+
+                let attack = clear.attack()
+                    + back_to_back_bonus as u32
+                    + combo.attack()
+                    + if perfect_clear { 10 } else { 0 };
+
+                let playing = Playing {
+                    score: self
+                        .score
+                        .add(points + rules.combo(combo.count(), level.num)),
+                    attack: self.attack.add(attack),
+                    combo,
+                    perfect_clears: if perfect_clear {
+                        self.perfect_clears.inc()
+                    } else {
+                        self.perfect_clears
+                    },
+                    back_to_back: if num_rows > 0 {
+                        clear.difficult()
+                    } else {
+                        self.back_to_back
+                    },
+                    banner: Banner::new(&banner).or_else(|| self.banner.clone()),
                     rows_removed: self.rows_removed.add(num_rows),
-                    mode: Mode::DroppingPiece(piece),
                     stack: stack,
+                    can_hold: true,
                     ..self.clone()
+                };
+
+                match self.settings.game_mode.line_goal() {
+                    Some(goal) if playing.rows_removed.num >= goal => Playing {
+                        game_over: Some(GameOverReason::GoalReached),
+                        ..playing
+                    },
+                    _ => playing.spawn_next(),
                 }
             }
-            Mode::DissolvingRows(timer) => Playing {
-                mode: Mode::DissolvingRows(timer - 1),
+            Mode::DissolvingRows(timer, spin) => Playing {
+                mode: Mode::DissolvingRows(timer - 1, *spin),
                 ..self.clone()
             },
         }
     }
 
     pub fn iterate(&self, controls: &Controls) -> Self {
-        if self.game_over {
+        if self.game_over.is_some() {
             return self.clone();
         }
 
-        self.increment_time().dissolve_rows(&controls)
+        let mut controls = controls.clone();
+        let ignored = self
+            .ignored
+            .iter()
+            .cloned()
+            .filter(|&button| controls.counter_mut(button).take().is_some())
+            .collect();
+        let controls = &controls;
+        let playing = Playing {
+            ignored,
+            ..self.increment_time()
+        };
+
+        match self.settings.game_mode.time_limit() {
+            Some(limit) if playing.time_elapsed.num >= limit => Playing {
+                game_over: Some(GameOverReason::TimeExpired),
+                ..playing
+            },
+            _ => playing.count_keys(controls).dissolve_rows(controls),
+        }
+    }
+
+    // every press of a piece control is a key, moves count towards the
+    // finesse of the piece until it is soft or sonic dropped
+    fn count_keys(&self, controls: &Controls) -> Self {
+        let pressed = |inputs: &[Option<u32>]| {
+            inputs.iter().filter(|&&input| input == Some(0)).count() as u32
+        };
+        let moves = pressed(&[
+            controls.left,
+            controls.right,
+            controls.primary,
+            controls.secondary,
+        ]);
+        let drops = pressed(&[
+            controls.up,
+            controls.down,
+            controls.hold,
+            controls.hard_drop,
+        ]);
+
+        Playing {
+            keys: self.keys.add(moves + drops),
+            piece_inputs: match self.mode {
+                Mode::DroppingPiece(..) if controls.down.is_some() || controls.up == Some(0) => {
+                    None
+                }
+                Mode::DroppingPiece(..) => self.piece_inputs.map(|inputs| inputs + moves),
+                Mode::DissolvingRows(..) => self.piece_inputs,
+            },
+            ..self.clone()
+        }
+    }
+
+    pub fn pieces_per_second(&self) -> f32 {
+        if self.time_elapsed.num == 0 {
+            0.0
+        } else {
+            self.pieces_dropped.num as f32 * 60.0 / self.time_elapsed.num as f32
+        }
+    }
+
+    fn per_minute(&self, num: u32) -> f32 {
+        if self.time_elapsed.num == 0 {
+            0.0
+        } else {
+            num as f32 * 3600.0 / self.time_elapsed.num as f32
+        }
+    }
+
+    pub fn lines_per_minute(&self) -> f32 {
+        self.per_minute(self.rows_removed.num)
+    }
+
+    pub fn attack_per_minute(&self) -> f32 {
+        self.per_minute(self.attack.num)
+    }
+
+    pub fn keys_per_piece(&self) -> f32 {
+        if self.pieces_dropped.num == 0 {
+            0.0
+        } else {
+            self.keys.num as f32 / self.pieces_dropped.num as f32
+        }
+    }
+
+    pub fn save(&self) -> Vec<u8> {
+        let mut bytes = SAVE_MAGIC.to_vec();
+
+        bytes.push(SAVE_VERSION);
+        put_settings(&mut bytes, &self.settings);
+        put_varint(&mut bytes, self.seed);
+        put_long(&mut bytes, self.rng.state);
+        self.randomizer.save(&mut bytes);
+
+        // runs of identical cells from the top row down
+        let cells: Vec<Option<Color>> = self
+            .stack
+            .bricks
+            .iter()
+            .flat_map(|row| row.iter().cloned())
+            .collect();
+
+        for run in cells.chunk_by(|a, b| a == b) {
+            put_varint(&mut bytes, run.len() as u32);
+            put_cell(&mut bytes, run[0]);
+        }
+
+        put_varint(&mut bytes, self.next.len() as u32);
+        for piece in &self.next {
+            put_piece(&mut bytes, piece);
+        }
+        match &self.hold {
+            Some(piece) => {
+                bytes.push(1);
+                put_piece(&mut bytes, piece);
+            }
+            None => bytes.push(0),
+        }
+        bytes.push(self.can_hold as u8);
+
+        match &self.mode {
+            Mode::DroppingPiece(piece, lock_delay) => {
+                bytes.push(0);
+                put_piece(&mut bytes, piece);
+                put_varint(&mut bytes, lock_delay.timer);
+                put_varint(&mut bytes, lock_delay.resets);
+                put_int(&mut bytes, lock_delay.lowest);
+            }
+            Mode::DissolvingRows(timer, spin) => {
+                bytes.push(1);
+                put_varint(&mut bytes, *timer);
+                bytes.push(match spin {
+                    Spin::None => 0,
+                    Spin::Mini => 1,
+                    Spin::Full => 2,
+                });
+            }
+        }
+
+        put_varint(&mut bytes, self.base_level.num);
+        put_varint(&mut bytes, self.rows_removed.num);
+        put_varint(&mut bytes, self.score.num);
+        put_varint(&mut bytes, self.pieces_dropped.num);
+        put_varint(&mut bytes, self.time_elapsed.num);
+        match self.combo.num {
+            Some(num) => {
+                bytes.push(1);
+                put_varint(&mut bytes, num);
+            }
+            None => bytes.push(0),
+        }
+        put_varint(&mut bytes, self.perfect_clears.num);
+        bytes.push(self.back_to_back as u8);
+        match &self.banner {
+            Some(banner) => {
+                bytes.push(1);
+                put_varint(&mut bytes, banner.lines.len() as u32);
+                for line in &banner.lines {
+                    put_string(&mut bytes, line);
+                }
+                put_varint(&mut bytes, banner.timer);
+            }
+            None => bytes.push(0),
+        }
+        put_varint(&mut bytes, self.gravity);
+        put_varint(&mut bytes, self.das_cut);
+        put_varint(&mut bytes, self.attack.num);
+        put_varint(&mut bytes, self.keys.num);
+        match self.piece_inputs {
+            Some(inputs) => {
+                bytes.push(1);
+                put_varint(&mut bytes, inputs);
+            }
+            None => bytes.push(0),
+        }
+        put_varint(&mut bytes, self.finesse_faults.num);
+        match &self.judgement {
+            Some(judgement) => {
+                bytes.push(1);
+                put_varint(&mut bytes, judgement.inputs);
+                put_varint(&mut bytes, judgement.optimal.len() as u32);
+                for &m in &judgement.optimal {
+                    bytes.push(Move::ALL.iter().position(|&other| other == m).unwrap() as u8);
+                }
+            }
+            None => bytes.push(0),
+        }
+        bytes.push(match self.game_over {
+            None => 0,
+            Some(GameOverReason::BlockOut) => 1,
+            Some(GameOverReason::LockOut) => 2,
+            Some(GameOverReason::GoalReached) => 3,
+            Some(GameOverReason::TimeExpired) => 4,
+        });
+        bytes
+    }
+
+    pub fn load(bytes: &[u8]) -> std::result::Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes, SAVE_MAGIC, SAVE_VERSION)?;
+        let settings = reader.settings()?;
+        let seed = reader.varint()?;
+        let rng = Pcg32 {
+            state: reader.long()?,
+        };
+        let randomizer = randomizer::load(&mut reader)?;
+
+        let mut stack = Stack::new();
+        let mut cell = 0;
+
+        while cell < 10 * STACK_ROWS {
+            let len = reader.varint()? as usize;
+            let color = load_cell(&mut reader)?;
+
+            if len == 0 || cell + len > 10 * STACK_ROWS {
+                return Err(DecodeError::Invalid);
+            }
+            for i in cell..cell + len {
+                stack.bricks[i / 10][i % 10] = color;
+            }
+            cell += len;
+        }
+
+        let next_len = reader.varint()? as usize;
+        if next_len != settings.next_queue {
+            return Err(DecodeError::Invalid);
+        }
+
+        let mut next = VecDeque::with_capacity(next_len);
+        for _ in 0..next_len {
+            next.push_back(load_waiting(&mut reader)?);
+        }
+        let hold = if reader.flag()? {
+            Some(load_waiting(&mut reader)?)
+        } else {
+            None
+        };
+        let can_hold = reader.flag()?;
+
+        let mode = match reader.byte()? {
+            0 => {
+                let piece = load_piece(&mut reader)?;
+
+                if !piece.fits(&stack) {
+                    return Err(DecodeError::Invalid);
+                }
+                Mode::DroppingPiece(
+                    piece,
+                    LockDelay {
+                        timer: reader.varint_in(0..=settings.lock_delay)?,
+                        resets: reader.varint_in(0..=MOVE_RESETS)?,
+                        lowest: match reader.int()? {
+                            lowest if ROWS.contains(&lowest) => lowest,
+                            _ => return Err(DecodeError::Invalid),
+                        },
+                    },
+                )
+            }
+            1 => Mode::DissolvingRows(
+                reader.varint_in(1..=30)?,
+                match reader.byte()? {
+                    0 => Spin::None,
+                    1 => Spin::Mini,
+                    2 => Spin::Full,
+                    _ => return Err(DecodeError::Invalid),
+                },
+            ),
+            _ => return Err(DecodeError::Invalid),
+        };
+
+        let base_level = Level::new(reader.varint_in(0..=GRAVITY.len() as u32)?);
+        let rows_removed = RowsRemoved {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let score = Score {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let pieces_dropped = PiecesDropped {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let time_elapsed = TimeElapsed {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let combo = Combo {
+            num: if reader.flag()? {
+                Some(reader.varint_in(0..=MAX_COUNT)?)
+            } else {
+                None
+            },
+        };
+        let perfect_clears = PerfectClears {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let back_to_back = reader.flag()?;
+        let banner = if reader.flag()? {
+            let len = reader.varint_in(1..=8)?;
+            let mut lines = vec![];
+
+            for _ in 0..len {
+                match reader.string()? {
+                    line if line.len() <= BANNER_WIDTH => lines.push(line),
+                    _ => return Err(DecodeError::Invalid),
+                }
+            }
+            Some(Banner {
+                lines,
+                timer: reader.varint_in(0..=BANNER_FRAMES)?,
+            })
+        } else {
+            None
+        };
+        let gravity = reader.varint_in(0..=0xffff)?;
+        let das_cut = reader.varint_in(0..=settings.handling.das_cut)?;
+        let attack = Attack {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let keys = Keys {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let piece_inputs = if reader.flag()? {
+            Some(reader.varint_in(0..=MAX_COUNT)?)
+        } else {
+            None
+        };
+        let finesse_faults = FinesseFaults {
+            num: reader.varint_in(0..=MAX_COUNT)?,
+        };
+        let judgement = if reader.flag()? {
+            let inputs = reader.varint_in(0..=MAX_COUNT)?;
+            let len = reader.varint_in(0..=Move::ALL.len() as u32)?;
+            let mut optimal = vec![];
+
+            for _ in 0..len {
+                optimal.push(
+                    *Move::ALL
+                        .get(reader.byte()? as usize)
+                        .ok_or(DecodeError::Invalid)?,
+                );
+            }
+            Some(Judgement { inputs, optimal })
+        } else {
+            None
+        };
+        let game_over = match reader.byte()? {
+            0 => None,
+            1 => Some(GameOverReason::BlockOut),
+            2 => Some(GameOverReason::LockOut),
+            3 => Some(GameOverReason::GoalReached),
+            4 => Some(GameOverReason::TimeExpired),
+            _ => return Err(DecodeError::Invalid),
+        };
+
+        reader.finish()?;
+
+        Ok(Playing {
+            stack,
+            next,
+            mode,
+            hold,
+            can_hold,
+            seed,
+            rng,
+            randomizer,
+            base_level,
+            rows_removed,
+            score,
+            pieces_dropped,
+            time_elapsed,
+            combo,
+            perfect_clears,
+            attack,
+            keys,
+            piece_inputs,
+            finesse_faults,
+            judgement,
+            ignored: vec![],
+            back_to_back,
+            banner,
+            gravity,
+            das_cut,
+            game_over,
+            settings,
+        })
+    }
+}
+
+const SAVE_MAGIC: &[u8; 4] = b"TETS";
+const SAVE_VERSION: u8 = 1;
+
+fn put_cell(bytes: &mut Vec<u8>, cell: Option<Color>) {
+    match cell {
+        Some(color) => {
+            bytes.push(1);
+            put_color(bytes, color);
+        }
+        None => bytes.push(0),
+    }
+}
+
+fn load_cell(reader: &mut Reader) -> std::result::Result<Option<Color>, DecodeError> {
+    if reader.flag()? {
+        Ok(Some(reader.color()?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn put_piece(bytes: &mut Vec<u8>, piece: &Piece) {
+    put_shape(bytes, piece.shape);
+    put_color(bytes, piece.color);
+    put_int(bytes, piece.offset.0);
+    put_int(bytes, piece.offset.1);
+    bytes.push(piece.orientation as u8);
+    match piece.kick {
+        Some(kick) => {
+            bytes.push(1);
+            put_varint(bytes, kick as u32);
+        }
+        None => bytes.push(0),
     }
 }
 
-use std::result::Result as Res;
+fn load_piece(reader: &mut Reader) -> std::result::Result<Piece, DecodeError> {
+    let piece = Piece::new(reader.shape()?, reader.color()?);
+    let offset = (reader.int()?, reader.int()?);
+    let orientation = reader.byte()? as usize;
+    let kick = if reader.flag()? {
+        Some(reader.varint()? as usize)
+    } else {
+        None
+    };
+
+    // keeps block positions from overflowing before bounds are checked
+    if orientation >= piece.orientations.len()
+        || !(-16..=16).contains(&offset.0)
+        || !ROWS.contains(&offset.1)
+    {
+        return Err(DecodeError::Invalid);
+    }
+
+    Ok(Piece {
+        offset,
+        orientation,
+        kick,
+        ..piece
+    })
+}
+
+// queued and held pieces are only ever placed at their spawn position
+fn load_waiting(reader: &mut Reader) -> std::result::Result<Piece, DecodeError> {
+    let piece = load_piece(reader)?;
+    let spawned = Piece::new(piece.shape, piece.color);
+
+    if piece.offset != spawned.offset || piece.orientation != 0 || piece.kick.is_some() {
+        return Err(DecodeError::Invalid);
+    }
+    Ok(piece)
+}
 
 impl Piece {
-    fn input_move_left(&self, controls: &Controls, stack: &Stack) -> Self {
-        self.try_moving_piece(stack, controls.left, controls.right, |piece| {
-            piece.move_left()
-        })
+    fn input_move_left(
+        &self,
+        controls: &Controls,
+        stack: &Stack,
+        handling: Handling,
+        das_cut: bool,
+    ) -> Self {
+        self.shift(
+            stack,
+            handling.shift(controls.left, controls.right, das_cut),
+            |piece| piece.move_left(),
+        )
     }
 
-    fn input_move_right(&self, controls: &Controls, stack: &Stack) -> Self {
-        self.try_moving_piece(stack, controls.right, controls.left, |piece| {
-            piece.move_right()
-        })
+    fn input_move_right(
+        &self,
+        controls: &Controls,
+        stack: &Stack,
+        handling: Handling,
+        das_cut: bool,
+    ) -> Self {
+        self.shift(
+            stack,
+            handling.shift(controls.right, controls.left, das_cut),
+            |piece| piece.move_right(),
+        )
     }
 
     fn input_rotate_left(&self, controls: &Controls, stack: &Stack) -> Self {
-        self.try_moving_piece(stack, controls.primary, controls.secondary, |piece| {
-            piece.rotate_left()
-        })
+        self.try_rotating_piece(stack, controls.primary, controls.secondary, false)
     }
 
     fn input_rotate_right(&self, controls: &Controls, stack: &Stack) -> Self {
-        self.try_moving_piece(stack, controls.secondary, controls.primary, |piece| {
-            piece.rotate_right()
-        })
+        self.try_rotating_piece(stack, controls.secondary, controls.primary, true)
     }
 
-    fn input_move_down(&self, controls: &Controls, stack: &Stack) -> Res<Self, Stack> {
-        if input_trigger(controls.down, controls.up, 20, 1) {
-            self.try_move_down(stack)
+    // a newly spawned piece drops one row straight away when it can
+    pub fn enter(&self, stack: &Stack) -> Self {
+        let moved_piece = self.move_down();
+
+        if moved_piece.fits(stack) {
+            moved_piece
         } else {
-            Ok(self.clone())
+            self.clone()
         }
     }
 
-    fn automatically_move_down(
-        &self,
-        time_elapsed: TimeElapsed,
-        base_level: Level,
-        rows_removed: RowsRemoved,
-        stack: &Stack,
-    ) -> Res<Self, Stack> {
-        if time_elapsed.num % (30 - 2 * base_level.adjusted_level(rows_removed).num) == 0 {
-            self.try_move_down(stack)
+    fn input_soft_drop(&self, controls: &Controls, stack: &Stack, handling: Handling) -> Self {
+        if controls.down.is_some() && handling.soft_drop == 0 {
+            self.drop(stack)
+        } else {
+            self.clone()
+        }
+    }
+
+    fn input_sonic_drop(&self, controls: &Controls, stack: &Stack) -> Self {
+        if controls.up == Some(0) {
+            self.drop(stack)
+        } else {
+            self.clone()
+        }
+    }
+
+    fn fall(&self, rows: u32, stack: &Stack) -> Self {
+        if rows == 0 {
+            self.clone()
+        } else {
+            let moved_piece = self.move_down();
+
+            if moved_piece.fits(stack) {
+                moved_piece.fall(rows - 1, stack)
+            } else {
+                self.clone()
+            }
+        }
+    }
+
+    pub fn shift<F: Fn(&Piece) -> Piece>(&self, stack: &Stack, cells: u32, m: F) -> Self {
+        if cells == 0 {
+            self.clone()
         } else {
-            Ok(self.clone())
+            let moved_piece = m(self);
+
+            if moved_piece.fits(stack) {
+                moved_piece.shift(stack, cells - 1, m)
+            } else {
+                self.clone()
+            }
         }
     }
 
-    fn try_moving_piece<F: FnOnce(&Piece) -> Piece>(
+    fn try_rotating_piece(
         &self,
         stack: &Stack,
         dir: Option<u32>,
         other_dir: Option<u32>,
-        m: F,
+        clockwise: bool,
     ) -> Self {
         if input_trigger(dir, other_dir, 20, 5) {
-            let moved_piece = m(self);
-
-            if moved_piece.inside_bounds() && !stack.overlaps(&moved_piece) {
-                moved_piece
-            } else {
-                self.clone()
-            }
+            self.rotate(stack, clockwise)
         } else {
             self.clone()
         }
     }
 
-    fn try_move_down(&self, stack: &Stack) -> Res<Self, Stack> {
+    // the first kick that fits, staying put when none do
+    pub fn rotate(&self, stack: &Stack, clockwise: bool) -> Self {
+        let rotated_piece = if clockwise {
+            self.rotate_right()
+        } else {
+            self.rotate_left()
+        };
+
+        self.kicks(clockwise)
+            .iter()
+            .enumerate()
+            .map(|(index, &translation)| rotated_piece.kick(index, translation))
+            .find(|kicked_piece| kicked_piece.fits(stack))
+            .unwrap_or_else(|| self.clone())
+    }
+
+    pub fn drop(&self, stack: &Stack) -> Self {
         let moved_piece = self.move_down();
 
-        if !moved_piece.inside_bounds() || stack.overlaps(&moved_piece) {
-            Err(stack.add_piece(self))
+        if moved_piece.fits(stack) {
+            moved_piece.drop(stack)
         } else {
-            Ok(moved_piece)
+            self.clone()
+        }
+    }
+
+    // three corner rule, upgraded from mini when the last kick test was used
+    fn spin(&self, stack: &Stack) -> Spin {
+        match self.kick {
+            Some(kick) if self.shape == Shape::T => {
+                let (x, y) = self.offset;
+                let corners = [(0, 0), (2, 0), (2, 2), (0, 2)];
+                let occupied: Vec<bool> = corners
+                    .iter()
+                    .map(|&(i, j)| stack.occupied(x + i, y + j))
+                    .collect();
+                let front = occupied[self.orientation] && occupied[(self.orientation + 1) % 4];
+
+                if occupied.iter().filter(|&&corner| corner).count() < 3 {
+                    Spin::None
+                } else if front || kick == 4 {
+                    Spin::Full
+                } else {
+                    Spin::Mini
+                }
+            }
+            _ => Spin::None,
+        }
+    }
+
+    fn rows_to(&self, piece: &Piece) -> u32 {
+        (piece.offset.1 - self.offset.1) as u32
+    }
+
+    pub fn fits(&self, stack: &Stack) -> bool {
+        self.inside_bounds() && !stack.overlaps(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetrust::game::Game;
+    use tetrust::randomizer::Bag;
+    use tetrust::replay::tests::play_randomly;
+    use tetrust::replay::Recording;
+
+    // games in progress sampled while playing pseudo random inputs
+    fn snapshots(seed: u32) -> Vec<Playing> {
+        let settings = Settings::new();
+        let recording = Recording::new(seed, &settings, &Controls::new());
+        let mut snapshots = vec![];
+
+        play_randomly(
+            &Game::start(&settings, seed),
+            &recording,
+            seed,
+            |frame, game| {
+                if frame % 37 == 0 {
+                    snapshots.extend(game.saved().cloned());
+                }
+            },
+        );
+        snapshots
+    }
+
+    #[test]
+    fn saved_games_round_trip() {
+        let snapshots: Vec<Playing> = (0..4).flat_map(snapshots).collect();
+
+        assert!(snapshots
+            .iter()
+            .any(|playing| matches!(playing.mode, Mode::DissolvingRows(..))));
+        assert!(snapshots.iter().any(|playing| playing.hold.is_some()));
+
+        for playing in snapshots {
+            let loaded = Playing::load(&playing.save()).unwrap();
+
+            assert_eq!(loaded.stack, playing.stack);
+            assert_eq!(format!("{:?}", loaded.next), format!("{:?}", playing.next));
+            assert_eq!(format!("{:?}", loaded.hold), format!("{:?}", playing.hold));
+            assert_eq!(format!("{:?}", loaded.mode), format!("{:?}", playing.mode));
+            assert_eq!(loaded.score, playing.score);
+            assert_eq!(loaded.rows_removed, playing.rows_removed);
+            assert_eq!(format!("{:?}", loaded), format!("{:?}", playing));
+
+            let controls = Controls::new();
+
+            assert_eq!(
+                loaded.iterate(&controls).save(),
+                playing.iterate(&controls).save()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_damaged_saves() {
+        let playing = snapshots(1).pop().unwrap();
+        let bytes = playing.save();
+
+        for len in 0..bytes.len() {
+            assert!(Playing::load(&bytes[..len]).is_err());
+        }
+
+        // whatever loads from a flipped byte must keep running and dealing
+        let mut hard_drop = Controls::new();
+        let idle = Controls::new();
+
+        hard_drop.press(Button::HardDrop);
+        for i in 0..bytes.len() {
+            let mut bytes = bytes.clone();
+
+            bytes[i] ^= 0xff;
+            if let Ok(mut loaded) = Playing::load(&bytes) {
+                for frame in 0..120 {
+                    loaded = loaded.iterate(if frame % 20 == 0 { &hard_drop } else { &idle });
+                }
+            }
+        }
+
+        let invalid = |playing: Playing| Playing::load(&playing.save()).err();
+
+        assert_eq!(
+            invalid(Playing {
+                mode: Mode::DissolvingRows(0, Spin::None),
+                ..playing.clone()
+            }),
+            Some(DecodeError::Invalid)
+        );
+        assert_eq!(
+            invalid(Playing {
+                randomizer: Box::new(Bag::new(1000)),
+                ..playing.clone()
+            }),
+            Some(DecodeError::Invalid)
+        );
+        assert_eq!(
+            invalid(Playing {
+                score: Score { num: u32::MAX },
+                ..playing.clone()
+            }),
+            Some(DecodeError::Invalid)
+        );
+        assert_eq!(
+            invalid(Playing {
+                gravity: 1 << 16,
+                ..playing.clone()
+            }),
+            Some(DecodeError::Invalid)
+        );
+
+        // a queued piece away from the spawn position is corrupt, and one
+        // outside the stack blocks out when dealt rather than panicking
+        let mut next = playing.next.clone();
+
+        next[0] = Piece {
+            offset: (-5, 0),
+            ..next[0].clone()
+        };
+        let misplaced = Playing {
+            next,
+            mode: Mode::DissolvingRows(1, Spin::None),
+            ..playing
+        };
+
+        assert_eq!(invalid(misplaced.clone()), Some(DecodeError::Invalid));
+        assert_eq!(
+            misplaced.iterate(&Controls::new()).game_over,
+            Some(GameOverReason::BlockOut)
+        );
+    }
+
+    fn practice() -> Playing {
+        let settings = Settings {
+            game_mode: GameMode::Finesse,
+            ..Settings::new()
+        };
+
+        Playing::new(Level::new(0), 7, Box::new(Bag::new(1)), &settings)
+    }
+
+    fn falling(playing: &Playing) -> Piece {
+        match &playing.mode {
+            Mode::DroppingPiece(piece, _) => piece.clone(),
+            Mode::DissolvingRows(..) => panic!("no piece in play"),
+        }
+    }
+
+    // one frame with the buttons held for the given number of frames
+    fn step(playing: &Playing, buttons: &[(Button, u32)]) -> Playing {
+        let mut controls = Controls::new();
+
+        for &(button, frames) in buttons {
+            *controls.counter_mut(button) = Some(frames);
+        }
+        playing.iterate(&controls)
+    }
+
+    #[test]
+    fn retries_faulted_pieces() {
+        let playing = practice();
+        let spawned = falling(&playing);
+        // tapping right then left returns the piece to where it spawned
+        let retried = [
+            vec![(Button::Right, 0)],
+            vec![(Button::Left, 0)],
+            vec![(Button::HardDrop, 0)],
+        ]
+        .iter()
+        .fold(playing.clone(), |playing, buttons| step(&playing, buttons));
+        let piece = falling(&retried);
+
+        assert_eq!(retried.stack, playing.stack);
+        assert_eq!(retried.pieces_dropped.num, 0);
+        assert_eq!(retried.finesse_faults.num, 1);
+        assert_eq!(format!("{:?}", retried.next), format!("{:?}", playing.next));
+        assert_eq!(piece.shape, spawned.shape);
+        assert_eq!(piece.offset, spawned.offset);
+        assert_eq!(retried.piece_inputs, Some(0));
+        assert!(retried.judgement.unwrap().fault());
+
+        let kept = Playing {
+            settings: Settings {
+                finesse_retry: false,
+                ..playing.settings.clone()
+            },
+            ..playing.clone()
+        };
+        let kept = step(
+            &step(&kept, &[(Button::Right, 0)]),
+            &[(Button::Left, 0), (Button::HardDrop, 0)],
+        );
+
+        assert_eq!(kept.pieces_dropped.num, 1);
+        assert!(!kept.stack.is_empty());
+    }
+
+    #[test]
+    fn ignores_keys_held_through_a_retry() {
+        let playing = step(
+            &step(&practice(), &[(Button::Right, 0)]),
+            &[(Button::Left, 0), (Button::HardDrop, 0)],
+        );
+        let spawned = falling(&playing);
+        let keys = playing.keys.num;
+
+        assert_eq!(playing.finesse_faults.num, 1);
+
+        // holding left well past das leaves the retried piece alone
+        let held = (1..40).fold(playing, |playing, frames| {
+            step(&playing, &[(Button::Left, frames)])
+        });
+
+        assert_eq!(falling(&held).offset.0, spawned.offset.0);
+        assert_eq!(held.piece_inputs, Some(0));
+        assert_eq!(held.keys.num, keys);
+
+        // once released it moves the piece again
+        let pressed = step(&step(&held, &[]), &[(Button::Left, 0)]);
+
+        assert_eq!(falling(&pressed).offset.0, spawned.offset.0 - 1);
+        assert_eq!(pressed.piece_inputs, Some(1));
+    }
+
+    // a stack whose bottom rows are drawn with X for bricks
+    fn stack(rows: &[&str]) -> Stack {
+        let mut stack = Stack::new();
+
+        for (j, row) in rows.iter().enumerate() {
+            for (i, cell) in row.chars().enumerate() {
+                if cell == 'X' {
+                    stack.bricks[STACK_ROWS - rows.len() + j][i] = Some(Color::Grey);
+                }
+            }
         }
+        stack
+    }
+
+    fn placed(shape: Shape, orientation: usize, offset: (i32, i32)) -> Piece {
+        Piece {
+            orientation,
+            offset,
+            ..Piece::new(shape, Color::Grey)
+        }
+    }
+
+    #[test]
+    fn kicks_jlstz_off_the_wall() {
+        // against the left wall the J turns flat by moving one column right
+        let rotated = placed(Shape::J, 1, (-1, 36)).rotate(&Stack::new(), true);
+
+        assert_eq!(rotated.orientation, 2);
+        assert_eq!(rotated.offset, (0, 36));
+        assert_eq!(rotated.kick, Some(1));
+    }
+
+    #[test]
+    fn kicks_i_up_past_the_stack() {
+        // the wall and a brick stop the first three tests so the I rises two rows
+        let stack = stack(&["......X...", ".........."]);
+        let rotated = placed(Shape::I, 1, (7, 36)).rotate(&stack, true);
+
+        assert_eq!(rotated.orientation, 2);
+        assert_eq!(rotated.offset, (6, 34));
+        assert_eq!(rotated.kick, Some(3));
+    }
+
+    // the spin a rotated piece locks with and the rows it clears
+    fn spun(piece: &Piece, stack: &Stack) -> (Spin, u32) {
+        (piece.spin(stack), stack.add_piece(piece).remove_lines().1)
+    }
+
+    #[test]
+    fn detects_t_spin_doubles() {
+        let stack = stack(&["..XXXXXXXX", "...XXXXXXX", "X.XXXXXXXX"]);
+        let rotated = placed(Shape::T, 1, (0, 37)).rotate(&stack, true);
+
+        assert_eq!(rotated.kick, Some(0));
+        assert_eq!(spun(&rotated, &stack), (Spin::Full, 2));
+
+        // dropped in rather than rotated it is no spin at all
+        let dropped = Piece {
+            kick: None,
+            ..rotated
+        };
+
+        assert_eq!(spun(&dropped, &stack), (Spin::None, 2));
+    }
+
+    #[test]
+    fn detects_mini_t_spins() {
+        // three corners, counting the floor, but only one in front
+        let stack = stack(&["..X.......", "...XXXXXXX"]);
+        let rotated = placed(Shape::T, 1, (-1, 37)).rotate(&stack, false);
+
+        assert_eq!(rotated.orientation, 0);
+        assert_eq!(rotated.offset, (0, 38));
+        assert_eq!(rotated.kick, Some(2));
+        assert_eq!(spun(&rotated, &stack), (Spin::Mini, 1));
+    }
+
+    #[test]
+    fn upgrades_the_last_kick_to_a_full_spin() {
+        let rows = [
+            ".X........",
+            "..........",
+            "X.XXXXXXXX",
+            "X..XXXXXXX",
+            "X.XXXXXXXX",
+        ];
+        let triple = stack(&rows);
+        let rotated = placed(Shape::T, 0, (1, 35)).rotate(&triple, true);
+
+        assert_eq!(rotated.offset, (0, 37));
+        assert_eq!(rotated.kick, Some(4));
+        assert_eq!(spun(&rotated, &triple), (Spin::Full, 3));
+
+        // with a front corner open the same kick still counts as full, where
+        // any other kick would only be a mini
+        let double = stack(&[rows[0], rows[1], rows[2], rows[3], "X..XXXXXXX"]);
+        let rotated = placed(Shape::T, 0, (1, 35)).rotate(&double, true);
+        let kicked_early = Piece {
+            kick: Some(0),
+            ..rotated.clone()
+        };
+
+        assert_eq!(rotated.kick, Some(4));
+        assert_eq!(spun(&rotated, &double), (Spin::Full, 2));
+        assert_eq!(spun(&kicked_early, &double), (Spin::Mini, 2));
     }
 }
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use tetrust::codec::{put_shape, put_varint, DecodeError, Reader};
+use tetrust::rng::Pcg32;
+use tetrust::shape::Shape;
+
+pub trait Randomizer: Debug {
+    fn next(&mut self, rng: &mut Pcg32) -> Shape;
+    fn box_clone(&self) -> Box<dyn Randomizer>;
+    // writes a tag followed by the state for `load`
+    fn save(&self, bytes: &mut Vec<u8>);
+}
+
+pub fn load(reader: &mut Reader) -> Result<Box<dyn Randomizer>, DecodeError> {
+    match reader.byte()? {
+        0 => {
+            // checked before sizing the bag so corrupt saves can't overflow
+            let copies = reader.varint_in(1..=4)? as usize;
+            let len = reader.varint()? as usize;
+
+            if len > copies * Shape::ALL.len() {
+                return Err(DecodeError::Invalid);
+            }
+
+            let mut shapes = Vec::with_capacity(copies * Shape::ALL.len());
+            for _ in 0..len {
+                shapes.push(reader.shape()?);
+            }
+            Ok(Box::new(Bag { copies, shapes }))
+        }
+        1 => Ok(Box::new(PureRandom)),
+        2 => {
+            let rerolls = reader.varint_in(0..=8)?;
+            let first = reader.flag()?;
+            let mut history = VecDeque::with_capacity(4);
+
+            for _ in 0..4 {
+                history.push_back(reader.shape()?);
+            }
+            Ok(Box::new(History {
+                rerolls,
+                history,
+                first,
+            }))
+        }
+        _ => Err(DecodeError::Invalid),
+    }
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+// deals every shape `copies` times in a random order before refilling
+#[derive(Clone, Debug)]
+pub struct Bag {
+    copies: usize,
+    shapes: Vec<Shape>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Bag {
+            copies,
+            shapes: Vec::with_capacity(copies * Shape::ALL.len()),
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self, rng: &mut Pcg32) -> Shape {
+        if self.shapes.is_empty() {
+            for _ in 0..self.copies {
+                self.shapes.extend_from_slice(&Shape::ALL);
+            }
+        }
+
+        let index = rng.gen_range(0, self.shapes.len() as u32) as usize;
+        self.shapes.swap_remove(index)
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self, bytes: &mut Vec<u8>) {
+        bytes.push(0);
+        put_varint(bytes, self.copies as u32);
+        put_varint(bytes, self.shapes.len() as u32);
+        for &shape in &self.shapes {
+            put_shape(bytes, shape);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PureRandom;
+
+impl Randomizer for PureRandom {
+    fn next(&mut self, rng: &mut Pcg32) -> Shape {
+        Shape::rand(rng)
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self, bytes: &mut Vec<u8>) {
+        bytes.push(1);
+    }
+}
+
+// TGM style, rerolls shapes found in the last four dealt and never starts with
+// an S, Z or O
+#[derive(Clone, Debug)]
+pub struct History {
+    rerolls: u32,
+    history: VecDeque<Shape>,
+    first: bool,
+}
+
+impl History {
+    pub fn new(rerolls: u32) -> Self {
+        History {
+            rerolls,
+            history: vec![Shape::Z, Shape::S, Shape::S, Shape::Z]
+                .into_iter()
+                .collect(),
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for History {
+    fn next(&mut self, rng: &mut Pcg32) -> Shape {
+        let shape = if self.first {
+            self.first = false;
+
+            match rng.gen_range(0, 4) {
+                0 => Shape::I,
+                1 => Shape::T,
+                2 => Shape::L,
+                _ => Shape::J,
+            }
+        } else {
+            let mut shape = Shape::rand(rng);
+
+            for _ in 0..self.rerolls {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+
+                shape = Shape::rand(rng);
+            }
+
+            shape
+        };
+
+        self.history.pop_front();
+        self.history.push_back(shape);
+        shape
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self, bytes: &mut Vec<u8>) {
+        bytes.push(2);
+        put_varint(bytes, self.rerolls);
+        bytes.push(self.first as u8);
+        for &shape in &self.history {
+            put_shape(bytes, shape);
+        }
+    }
+}
@@ -1,6 +1,12 @@
-use tetrust::controls::Controls;
-use tetrust::game::Game;
+use tetrust::controls::{Button, Controls};
+use tetrust::game::{Event, Game};
+use tetrust::game_mode::GameMode;
+use tetrust::high_scores::{Entry, HighScores, LocalStorage};
 use tetrust::menu::Menu;
+use tetrust::piece::Grid;
+use tetrust::replay::{Action, Player, Recording};
+use tetrust::rng::Pcg32;
+use tetrust::settings::Settings;
 use REGULATOR;
 
 #[derive(Clone, Debug)]
@@ -8,6 +14,23 @@ pub struct Regulator {
     t: f32,
     pub controls: Controls,
     pub game: Game,
+    pub events: Vec<Event>,
+    entropy: Pcg32,
+    // inputs of the game in progress
+    recording: Option<Recording>,
+    // the last finished game
+    pub replay: Option<Recording>,
+    // while watching a replay the live controls drive the player instead
+    player: Option<Player>,
+    pub high_scores: HighScores,
+    // mode whose high score board is open
+    board: Option<GameMode>,
+    // recorded with high scores
+    pub name: String,
+    // board and rank the last finished game placed at
+    placed: Option<(GameMode, usize)>,
+    // days since 1970-01-01, kept current by the page
+    pub date: u32,
 }
 
 impl Regulator {
@@ -16,7 +39,17 @@ impl Regulator {
             REGULATOR.get_or_insert_with(|| Regulator {
                 t,
                 controls: Controls::new(),
-                game: Game::InMenu(Menu::NewGame),
+                game: Game::InMenu(Menu::NewGame, Settings::new(), None),
+                events: vec![],
+                entropy: Pcg32::new(t.to_bits()),
+                recording: None,
+                replay: None,
+                player: None,
+                high_scores: HighScores::new(Box::new(LocalStorage)),
+                board: None,
+                name: "PLAYER".to_string(),
+                placed: None,
+                date: 0,
             })
         }
     }
@@ -26,21 +59,181 @@ impl Regulator {
         let d = 1000.0 / iterations_per_second;
 
         if t - self.t >= 1000.0 {
-            self.controls.iterate();
-            Regulator {
-                t,
-                game: self.game.iterate(&self.controls),
-                controls: self.controls.iterate(),
-            }
+            Regulator { t, ..self.step() }
         } else if t - self.t >= d {
             Regulator {
                 t: self.t + d,
-                game: self.game.iterate(&self.controls),
-                controls: self.controls.iterate(),
+                ..self.step()
             }
             .iterate(t)
         } else {
             self.clone()
         }
     }
+
+    fn step(&self) -> Self {
+        if let Some(player) = &self.player {
+            return Regulator {
+                controls: self.controls.iterate(),
+                player: player.iterate(&self.controls),
+                ..self.clone()
+            };
+        }
+
+        if let Some(mode) = self.board {
+            let controls = &self.controls;
+
+            return Regulator {
+                controls: controls.iterate(),
+                board: if controls.primary == Some(0)
+                    || controls.secondary == Some(0)
+                    || controls.pause == Some(0)
+                {
+                    None
+                } else if controls.left == Some(0) {
+                    Some(mode.previous())
+                } else if controls.right == Some(0) {
+                    Some(mode.next())
+                } else {
+                    Some(mode)
+                },
+                ..self.clone()
+            };
+        }
+
+        let mut entropy = self.entropy.clone();
+        let (game, event) = self.game.iterate(&self.controls, entropy.next_u32());
+        let controls = self.controls.iterate();
+
+        let mut recording = self.recording.clone();
+        let mut replay = self.replay.clone();
+        let mut player = None;
+        let mut high_scores = self.high_scores.clone();
+        let mut board = None;
+        let mut placed = self.placed;
+
+        if let Some(recording) = &mut recording {
+            recording.advance();
+        }
+
+        match event {
+            Some(Event::Ready) | Some(Event::Results) | Some(Event::Quit) => {
+                if let Some(finished) = recording.take() {
+                    replay = Some(finished);
+                }
+            }
+            Some(Event::Replay) => player = replay.as_ref().map(Player::new),
+            Some(Event::HighScores) => board = Some(game.settings().game_mode),
+            _ => (),
+        }
+
+        match (event, &game) {
+            (Some(Event::Ready), Game::Countdown(playing, _)) => {
+                recording = Some(Recording::new(playing.seed, &playing.settings, &controls));
+            }
+            (Some(Event::Ready), Game::Paused(playing, _)) => {
+                recording = Some(Recording::resumed(playing, &controls));
+            }
+            (Some(Event::Results), Game::Results(playing, _)) => {
+                let mode = playing.settings.game_mode;
+
+                placed = Entry::new(&self.name, self.date, playing)
+                    .and_then(|entry| high_scores.submit(mode, entry))
+                    .map(|rank| (mode, rank));
+            }
+            _ => (),
+        }
+
+        Regulator {
+            controls,
+            game,
+            events: self
+                .events
+                .iter()
+                .cloned()
+                .chain(event)
+                .chain(match (event, placed) {
+                    (Some(Event::Results), Some(_)) => Some(Event::NewHighScore),
+                    _ => None,
+                })
+                .collect(),
+            entropy,
+            recording,
+            replay,
+            player,
+            high_scores,
+            board,
+            placed,
+            ..self.clone()
+        }
+    }
+
+    pub fn key_down(&mut self, key_code: u32) {
+        self.controls.key_down(key_code);
+        self.record(key_code, Action::Press);
+    }
+
+    pub fn key_up(&mut self, key_code: u32) {
+        self.controls.key_up(key_code);
+        self.record(key_code, Action::Release);
+    }
+
+    fn record(&mut self, key_code: u32, action: fn(Button) -> Action) {
+        if let (Some(recording), Some(button)) =
+            (&mut self.recording, Button::from_key_code(key_code))
+        {
+            recording.record(action(button));
+        }
+    }
+
+    // the name is kept for later games and given to the last game's entry
+    pub fn rename(&mut self, name: &str) {
+        self.name = name.to_string();
+        if let Some((mode, rank)) = self.placed {
+            self.high_scores.rename(mode, rank, name);
+        }
+    }
+
+    pub fn watch(&mut self, recording: &Recording) {
+        self.player = Some(Player::new(recording));
+    }
+
+    // losing focus pauses the game, or the replay while one is playing
+    pub fn blur(&mut self) {
+        if let Some(player) = &self.player {
+            self.player = Some(player.pause());
+            return;
+        }
+
+        let (game, event) = self.game.pause();
+
+        if let (Some(recording), Some(_)) = (&mut self.recording, event) {
+            recording.record(Action::Blur);
+        }
+        self.game = game;
+        self.events.extend(event);
+    }
+
+    // tile atlas of the theme on screen, a replay shows its own
+    pub fn atlas(&self) -> &'static str {
+        self.player
+            .as_ref()
+            .map_or(&self.game, Player::game)
+            .settings()
+            .theme
+            .atlas()
+    }
+
+    pub fn grid(&self) -> Grid {
+        match (&self.player, self.board) {
+            (Some(player), _) => player.grid(),
+            (None, Some(mode)) => {
+                let mut grid = self.game.grid();
+
+                self.high_scores.draw(&mut grid, mode);
+                grid
+            }
+            (None, None) => self.game.grid(),
+        }
+    }
 }
@@ -0,0 +1,263 @@
+use grid::Pos;
+use std::rc::Rc;
+use tetrust::controls::{Button, Controls};
+use tetrust::game::{input_trigger, Game};
+use tetrust::piece::Grid;
+use tetrust::playing::Playing;
+use tetrust::settings::Settings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Press(Button),
+    Release(Button),
+    // the page lost focus and paused the game
+    Blur,
+}
+
+// an action applied just before the given frame is iterated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Input {
+    pub frame: u32,
+    pub action: Action,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    pub seed: u32,
+    pub settings: Settings,
+    // buttons already held when the game was created
+    pub controls: Controls,
+    pub inputs: Vec<Input>,
+    pub frames: u32,
+    // the saved game a resumed game picked up from, None when the game was
+    // recorded from its start
+    pub saved: Option<Vec<u8>>,
+}
+
+impl Recording {
+    pub fn new(seed: u32, settings: &Settings, controls: &Controls) -> Self {
+        Recording {
+            seed,
+            settings: settings.clone(),
+            controls: Controls {
+                digit: None,
+                ..controls.clone()
+            },
+            inputs: vec![],
+            frames: 0,
+            saved: None,
+        }
+    }
+
+    pub fn resumed(playing: &Playing, controls: &Controls) -> Self {
+        Recording {
+            saved: Some(playing.save()),
+            ..Recording::new(playing.seed, &playing.settings, controls)
+        }
+    }
+
+    // the game as it stood on the first recorded frame
+    pub fn game(&self) -> Game {
+        match self.saved.as_ref().map(|bytes| Playing::load(bytes)) {
+            Some(Ok(playing)) => Game::resume(&playing),
+            _ => Game::start(&self.settings, self.seed),
+        }
+    }
+
+    pub fn record(&mut self, action: Action) {
+        self.inputs.push(Input {
+            frame: self.frames,
+            action,
+        });
+    }
+
+    pub fn advance(&mut self) {
+        self.frames += 1;
+    }
+}
+
+const FAST_FORWARD: u32 = 4;
+
+// plays a recording back through Game::iterate, driven by the live controls
+#[derive(Clone, Debug)]
+pub struct Player {
+    recording: Rc<Recording>,
+    game: Game,
+    controls: Controls,
+    frame: u32,
+    input: usize,
+    paused: bool,
+    // frames stepped per tick
+    speed: u32,
+}
+
+impl Player {
+    pub fn new(recording: &Recording) -> Self {
+        Player {
+            recording: Rc::new(recording.clone()),
+            game: recording.game(),
+            controls: recording.controls.clone(),
+            frame: 0,
+            input: 0,
+            paused: false,
+            speed: 1,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn finished(&self) -> bool {
+        self.frame >= self.recording.frames
+    }
+
+    // pause toggles, right fast forwards, primary steps a frame while paused
+    // and secondary leaves the replay
+    pub fn iterate(&self, controls: &Controls) -> Option<Self> {
+        if controls.secondary == Some(0) || (self.finished() && controls.primary == Some(0)) {
+            return None;
+        }
+
+        let paused = if controls.pause == Some(0) {
+            !self.paused
+        } else {
+            self.paused
+        };
+
+        let speed = if paused {
+            if input_trigger(controls.primary, None, 30, 5) {
+                1
+            } else {
+                0
+            }
+        } else if controls.right.is_some() {
+            FAST_FORWARD
+        } else {
+            1
+        };
+
+        let mut player = Player {
+            paused,
+            speed,
+            ..self.clone()
+        };
+
+        for _ in 0..speed {
+            if player.finished() {
+                break;
+            }
+            player = player.step();
+        }
+
+        Some(player)
+    }
+
+    pub fn pause(&self) -> Self {
+        Player {
+            paused: true,
+            ..self.clone()
+        }
+    }
+
+    fn step(&self) -> Self {
+        let mut game = self.game.clone();
+        let mut controls = self.controls.clone();
+        let inputs = &self.recording.inputs[self.input..];
+        let applied = inputs
+            .iter()
+            .take_while(|input| input.frame == self.frame)
+            .count();
+
+        for input in &inputs[..applied] {
+            match input.action {
+                Action::Press(button) => controls.press(button),
+                Action::Release(button) => controls.release(button),
+                Action::Blur => game = game.pause().0,
+            }
+        }
+
+        Player {
+            game: game.iterate(&controls, 0).0,
+            controls: controls.iterate(),
+            frame: self.frame + 1,
+            input: self.input + applied,
+            ..self.clone()
+        }
+    }
+
+    pub fn grid(&self) -> Grid {
+        let mut grid = self.game.grid();
+
+        grid.draw_string(Pos(15, 2), "REPLAY");
+        grid.draw_string(
+            Pos(23, 2),
+            &if self.finished() {
+                "END".to_string()
+            } else if self.paused {
+                "PAUSED".to_string()
+            } else if self.speed > 1 {
+                format!("x{}", self.speed)
+            } else {
+                format!("{}s", self.frame / 60)
+            },
+        );
+        grid
+    }
+}
+
+// fixture shared by the replay and save format tests
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use tetrust::rng::Pcg32;
+
+    const BUTTONS: [Button; 8] = [
+        Button::Up,
+        Button::Down,
+        Button::Left,
+        Button::Right,
+        Button::Primary,
+        Button::Secondary,
+        Button::Hold,
+        Button::HardDrop,
+    ];
+
+    // plays game with pseudo random inputs the way the regulator records
+    // them, visiting every frame until the game is over
+    pub fn play_randomly(
+        game: &Game,
+        recording: &Recording,
+        seed: u32,
+        mut visit: impl FnMut(u32, &Game),
+    ) -> (Recording, Game) {
+        let mut rng = Pcg32::new(seed);
+        let mut controls = recording.controls.clone();
+        let mut recording = recording.clone();
+        let mut game = game.clone();
+
+        for frame in 0..20000 {
+            for _ in 0..rng.gen_range(0, 3) {
+                let button = BUTTONS[rng.gen_range(0, BUTTONS.len() as u32) as usize];
+
+                if rng.gen_range(0, 2) == 0 {
+                    controls.press(button);
+                    recording.record(Action::Press(button));
+                } else {
+                    controls.release(button);
+                    recording.record(Action::Release(button));
+                }
+            }
+
+            game = game.iterate(&controls, 0).0;
+            controls = controls.iterate();
+            recording.advance();
+            visit(frame, &game);
+
+            if let Game::Over(..) = game {
+                break;
+            }
+        }
+        (recording, game)
+    }
+}
@@ -0,0 +1,297 @@
+use tetrust::codec::{self, put_bytes, put_settings, put_varint, DecodeError, Reader};
+use tetrust::controls::{Button, Controls};
+use tetrust::playing::Playing;
+use tetrust::replay::{Action, Input, Recording};
+
+// layout of version 1, numbers are varints unless noted:
+//   "TETR", version byte, seed, settings
+//   saved game flag, then the length and bytes of the save a resumed game
+//   picked up from
+//   held buttons: bit mask, then a counter per set bit
+//   frames, input count, then per input the frames since the previous
+//   input followed by an action byte
+const MAGIC: &[u8; 4] = b"TETR";
+const VERSION: u8 = 1;
+
+const RELEASE: u8 = 0x10;
+const BLUR: u8 = 0x20;
+
+pub fn encode(recording: &Recording) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+
+    bytes.push(VERSION);
+    put_varint(&mut bytes, recording.seed);
+    put_settings(&mut bytes, &recording.settings);
+    match &recording.saved {
+        Some(saved) => {
+            bytes.push(1);
+            put_bytes(&mut bytes, saved);
+        }
+        None => bytes.push(0),
+    }
+
+    let mut controls = recording.controls.clone();
+    let held: Vec<u32> = Button::ALL
+        .iter()
+        .filter_map(|&button| *controls.counter_mut(button))
+        .collect();
+    let mask = Button::ALL
+        .iter()
+        .enumerate()
+        .filter(|&(_, &button)| controls.counter_mut(button).is_some())
+        .fold(0, |mask, (i, _)| mask | 1 << i);
+
+    put_varint(&mut bytes, mask);
+    for counter in held {
+        put_varint(&mut bytes, counter);
+    }
+
+    put_varint(&mut bytes, recording.frames);
+    put_varint(&mut bytes, recording.inputs.len() as u32);
+
+    let mut frame = 0;
+    for input in &recording.inputs {
+        put_varint(&mut bytes, input.frame - frame);
+        bytes.push(match input.action {
+            Action::Press(button) => button_index(button),
+            Action::Release(button) => RELEASE | button_index(button),
+            Action::Blur => BLUR,
+        });
+        frame = input.frame;
+    }
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Recording, DecodeError> {
+    let mut reader = Reader::new(bytes, MAGIC, VERSION)?;
+    let seed = reader.varint()?;
+    let settings = reader.settings()?;
+    let saved = if reader.flag()? {
+        let saved = reader.bytes()?;
+
+        Playing::load(&saved)?;
+        Some(saved)
+    } else {
+        None
+    };
+
+    let mask = reader.varint()?;
+    if mask >> Button::ALL.len() != 0 {
+        return Err(DecodeError::Invalid);
+    }
+
+    let mut controls = Controls::new();
+    for (i, &button) in Button::ALL.iter().enumerate() {
+        if mask & 1 << i != 0 {
+            *controls.counter_mut(button) = Some(reader.varint()?);
+        }
+    }
+
+    let frames = reader.varint()?;
+    let count = reader.varint()?;
+    let mut inputs = vec![];
+    let mut frame: u32 = 0;
+
+    for _ in 0..count {
+        frame = frame
+            .checked_add(reader.varint()?)
+            .ok_or(DecodeError::Invalid)?;
+
+        let action = match reader.byte()? {
+            BLUR => Action::Blur,
+            byte => {
+                let button = *Button::ALL
+                    .get((byte & !RELEASE) as usize)
+                    .ok_or(DecodeError::Invalid)?;
+
+                if byte & RELEASE != 0 {
+                    Action::Release(button)
+                } else {
+                    Action::Press(button)
+                }
+            }
+        };
+
+        inputs.push(Input { frame, action });
+    }
+
+    reader.finish()?;
+    if frame > frames {
+        return Err(DecodeError::Invalid);
+    }
+
+    Ok(Recording {
+        seed,
+        settings,
+        controls,
+        inputs,
+        frames,
+        saved,
+    })
+}
+
+pub fn to_base64(recording: &Recording) -> String {
+    codec::to_base64(&encode(recording))
+}
+
+pub fn from_base64(code: &str) -> Result<Recording, DecodeError> {
+    decode(&codec::from_base64(code)?)
+}
+
+fn button_index(button: Button) -> u8 {
+    Button::ALL.iter().position(|&b| b == button).unwrap() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetrust::game::Game;
+    use tetrust::game_mode::GameMode;
+    use tetrust::playing::Playing;
+    use tetrust::replay::tests::play_randomly;
+    use tetrust::replay::Player;
+    use tetrust::settings::{Generator, Handling, LockReset, Opposing, Settings};
+    use tetrust::theme::Theme;
+
+    // plays a game with pseudo random inputs the way the regulator records it
+    fn record(seed: u32, settings: &Settings) -> (Recording, Playing) {
+        let mut controls = Controls::new();
+
+        controls.press(Button::Left);
+
+        let recording = Recording::new(seed, settings, &controls);
+
+        match play_randomly(&Game::start(settings, seed), &recording, seed, |_, _| ()) {
+            (recording, Game::Over(playing, _)) => (recording, playing),
+            _ => panic!("game did not end"),
+        }
+    }
+
+    fn replay(recording: &Recording) -> Playing {
+        let mut player = Player::new(recording);
+
+        while !player.finished() {
+            player = player.iterate(&Controls::new()).unwrap();
+        }
+
+        match player.game() {
+            Game::Over(playing, _) => playing.clone(),
+            _ => panic!("replay did not end"),
+        }
+    }
+
+    fn settings() -> Vec<Settings> {
+        vec![
+            Settings::new(),
+            Settings {
+                game_mode: GameMode::Classic,
+                seed: Some(1234567),
+                lock_reset: LockReset::Step,
+                theme: Theme::Random,
+                randomizer: Generator::History,
+                handling: Handling {
+                    das: 7,
+                    arr: 0,
+                    soft_drop: 0,
+                    das_cut: 3,
+                    opposing: Opposing::Cancel,
+                },
+                ..Settings::new()
+            },
+            Settings {
+                game_mode: GameMode::Sprint,
+                lock_delay: 300,
+                lock_reset: LockReset::Infinity,
+                next_queue: 1,
+                randomizer: Generator::Bag14,
+                theme: Theme::GameBoy,
+                ..Settings::new()
+            },
+        ]
+    }
+
+    #[test]
+    fn decoded_replay_reproduces_game() {
+        for (seed, settings) in settings().iter().enumerate() {
+            let (recording, playing) = record(seed as u32 * 7919, settings);
+            let decoded = decode(&encode(&recording)).unwrap();
+            let replayed = replay(&decoded);
+
+            assert_eq!(decoded, recording);
+            assert_eq!(replayed.score, playing.score);
+            assert_eq!(replayed.rows_removed, playing.rows_removed);
+            assert_eq!(replayed.stack, playing.stack);
+        }
+    }
+
+    // games continued from a save are replayed from that save
+    #[test]
+    fn resumed_replay_reproduces_game() {
+        let settings = Settings::new();
+        let mut saved = None;
+
+        play_randomly(
+            &Game::start(&settings, 5),
+            &Recording::new(5, &settings, &Controls::new()),
+            5,
+            |frame, game| {
+                if let (Game::Playing(playing), 200) = (game, frame) {
+                    saved = Some(playing.save());
+                }
+            },
+        );
+
+        let resumed = Playing::load(&saved.unwrap()).unwrap();
+        let recording = Recording::resumed(&resumed, &Controls::new());
+        let (recording, playing) =
+            match play_randomly(&Game::resume(&resumed), &recording, 6, |_, _| ()) {
+                (recording, Game::Over(playing, _)) => (recording, playing),
+                _ => panic!("game did not end"),
+            };
+        let decoded = decode(&encode(&recording)).unwrap();
+        let replayed = replay(&decoded);
+
+        assert_eq!(decoded, recording);
+        assert_eq!(replayed.score, playing.score);
+        assert_eq!(replayed.rows_removed, playing.rows_removed);
+        assert_eq!(replayed.stack, playing.stack);
+    }
+
+    #[test]
+    fn base64_round_trips() {
+        for (seed, settings) in settings().iter().enumerate() {
+            let (mut recording, _) = record(seed as u32, settings);
+
+            recording.record(Action::Blur);
+
+            let code = to_base64(&recording);
+
+            assert!(code
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+            assert_eq!(from_base64(&code).unwrap(), recording);
+        }
+    }
+
+    #[test]
+    fn rejects_damaged_replays() {
+        let (recording, _) = record(1, &Settings::new());
+        let bytes = encode(&recording);
+
+        assert_eq!(
+            decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(decode(b"TRIS"), Err(DecodeError::Magic));
+        assert_eq!(
+            decode(&[b'T', b'E', b'T', b'R', VERSION + 1]),
+            Err(DecodeError::Version(VERSION + 1))
+        );
+        assert_eq!(from_base64("not base64!"), Err(DecodeError::Base64));
+
+        let mut tampered = recording.clone();
+
+        tampered.settings.handling.soft_drop = 100_000;
+        assert_eq!(decode(&encode(&tampered)), Err(DecodeError::Invalid));
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use tetrust::clear::Clear;
+
+// levels are counted from 0
+pub trait ScoringRules: Debug {
+    fn clear(&self, clear: Clear, rows: u32, level: u32) -> u32;
+
+    fn back_to_back(&self, points: u32) -> u32 {
+        points
+    }
+
+    fn combo(&self, _count: u32, _level: u32) -> u32 {
+        0
+    }
+
+    fn perfect_clear(&self, _rows: u32, _back_to_back: bool, _level: u32) -> u32 {
+        0
+    }
+
+    fn soft_drop(&self, cells: u32) -> u32 {
+        cells
+    }
+
+    fn hard_drop(&self, cells: u32) -> u32 {
+        2 * cells
+    }
+}
+
+// guideline perfect clear bonus before the level multiplier
+fn perfect_clear_points(rows: u32, back_to_back: bool) -> u32 {
+    match rows {
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        _ if back_to_back => 3200,
+        _ => 2000,
+    }
+}
+
+#[derive(Debug)]
+pub struct Guideline;
+
+impl ScoringRules for Guideline {
+    fn clear(&self, clear: Clear, _rows: u32, level: u32) -> u32 {
+        clear.points() * (level + 1)
+    }
+
+    fn back_to_back(&self, points: u32) -> u32 {
+        points * 3 / 2
+    }
+
+    fn combo(&self, count: u32, level: u32) -> u32 {
+        50 * count * (level + 1)
+    }
+
+    fn perfect_clear(&self, rows: u32, back_to_back: bool, level: u32) -> u32 {
+        perfect_clear_points(rows, back_to_back) * (level + 1)
+    }
+}
+
+// original NES table, spins and bonuses are not rewarded
+#[derive(Debug)]
+pub struct Classic;
+
+impl ScoringRules for Classic {
+    fn clear(&self, _clear: Clear, rows: u32, level: u32) -> u32 {
+        let points = match rows {
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            4 => 1200,
+            _ => 0,
+        };
+
+        points * (level + 1)
+    }
+
+    // the nes never rewarded perfect clears, they are still counted and
+    // announced
+    fn perfect_clear(&self, _rows: u32, _back_to_back: bool, _level: u32) -> u32 {
+        0
+    }
+}
+
+// flat points per row
+#[derive(Debug)]
+pub struct Linear;
+
+impl ScoringRules for Linear {
+    fn clear(&self, _clear: Clear, rows: u32, level: u32) -> u32 {
+        rows * (100 + 10 * level)
+    }
+
+    // the guideline bonus, growing by a tenth per level like the rows do
+    fn perfect_clear(&self, rows: u32, back_to_back: bool, level: u32) -> u32 {
+        perfect_clear_points(rows, back_to_back) * (10 + level) / 10
+    }
+}
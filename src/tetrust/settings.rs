@@ -0,0 +1,203 @@
+use std::fmt::{Display, Formatter, Result};
+use std::ops::RangeInclusive;
+use tetrust::game_mode::GameMode;
+use tetrust::randomizer::{Bag, History, PureRandom, Randomizer};
+use tetrust::theme::Theme;
+
+// ranges the options menu allows, decoded settings outside them are rejected
+pub const LOCK_DELAY: RangeInclusive<u32> = 0..=600;
+pub const MOVE_RESETS: u32 = 15;
+pub const NEXT_QUEUE: RangeInclusive<usize> = 1..=6;
+pub const DAS: RangeInclusive<u32> = 0..=30;
+pub const ARR: RangeInclusive<u32> = 0..=10;
+pub const SOFT_DROP: RangeInclusive<u32> = 0..=40;
+pub const DAS_CUT: RangeInclusive<u32> = 0..=20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockReset {
+    Infinity,
+    Step,
+    Move(u32),
+}
+
+impl LockReset {
+    pub fn next(self) -> Self {
+        match self {
+            LockReset::Move(_) => LockReset::Step,
+            LockReset::Step => LockReset::Infinity,
+            LockReset::Infinity => LockReset::Move(MOVE_RESETS),
+        }
+    }
+}
+
+impl Display for LockReset {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            LockReset::Infinity => write!(f, "Infinity"),
+            LockReset::Step => write!(f, "Step"),
+            LockReset::Move(limit) => write!(f, "Move {}", limit),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opposing {
+    LastKeyWins,
+    Cancel,
+}
+
+impl Opposing {
+    pub fn next(self) -> Self {
+        match self {
+            Opposing::LastKeyWins => Opposing::Cancel,
+            Opposing::Cancel => Opposing::LastKeyWins,
+        }
+    }
+}
+
+impl Display for Opposing {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Opposing::LastKeyWins => write!(f, "Last Wins"),
+            Opposing::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+// which live statistics the hud panel shows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsPanel {
+    Off,
+    Pace,
+    Full,
+}
+
+impl StatsPanel {
+    pub fn next(self) -> Self {
+        match self {
+            StatsPanel::Off => StatsPanel::Pace,
+            StatsPanel::Pace => StatsPanel::Full,
+            StatsPanel::Full => StatsPanel::Off,
+        }
+    }
+}
+
+impl Display for StatsPanel {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            StatsPanel::Off => write!(f, "Off"),
+            StatsPanel::Pace => write!(f, "Pace"),
+            StatsPanel::Full => write!(f, "Full"),
+        }
+    }
+}
+
+// how the sequence of pieces is dealt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generator {
+    Bag7,
+    Bag14,
+    Random,
+    History,
+}
+
+impl Generator {
+    pub fn next(self) -> Self {
+        match self {
+            Generator::Bag7 => Generator::Bag14,
+            Generator::Bag14 => Generator::Random,
+            Generator::Random => Generator::History,
+            Generator::History => Generator::Bag7,
+        }
+    }
+
+    pub fn randomizer(self) -> Box<dyn Randomizer> {
+        match self {
+            Generator::Bag7 => Box::new(Bag::new(1)),
+            Generator::Bag14 => Box::new(Bag::new(2)),
+            Generator::Random => Box::new(PureRandom),
+            Generator::History => Box::new(History::new(4)),
+        }
+    }
+}
+
+impl Display for Generator {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Generator::Bag7 => write!(f, "7-Bag"),
+            Generator::Bag14 => write!(f, "14-Bag"),
+            Generator::Random => write!(f, "Random"),
+            Generator::History => write!(f, "History"),
+        }
+    }
+}
+
+// all timings are in frames, an arr or soft drop of 0 is instant
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Handling {
+    pub das: u32,
+    pub arr: u32,
+    pub soft_drop: u32,
+    pub das_cut: u32,
+    pub opposing: Opposing,
+}
+
+impl Handling {
+    pub fn new() -> Self {
+        Handling {
+            das: 10,
+            arr: 2,
+            soft_drop: 20,
+            das_cut: 0,
+            opposing: Opposing::LastKeyWins,
+        }
+    }
+
+    // number of cells to shift this frame while holding input
+    pub fn shift(self, input: Option<u32>, opposing_input: Option<u32>, das_cut: bool) -> u32 {
+        match (input, opposing_input) {
+            (None, _) => 0,
+            (Some(_), Some(_)) if self.opposing == Opposing::Cancel => 0,
+            (Some(x), Some(y)) if x >= y => 0,
+            (Some(0), _) => 1,
+            (Some(x), _) if x < self.das || das_cut => 0,
+            (Some(_), _) if self.arr == 0 => u32::MAX,
+            (Some(x), _) => (x - self.das).is_multiple_of(self.arr) as u32,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub game_mode: GameMode,
+    // None deals a random seed for every game
+    pub seed: Option<u32>,
+    pub ghost: bool,
+    pub stats: StatsPanel,
+    // a piece placed with a finesse fault is taken back in finesse mode
+    pub finesse_retry: bool,
+    pub lock_delay: u32,
+    pub lock_reset: LockReset,
+    pub next_queue: usize,
+    pub theme: Theme,
+    pub randomizer: Generator,
+    pub handling: Handling,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings {
+            game_mode: GameMode::Marathon,
+            seed: None,
+            ghost: true,
+            stats: StatsPanel::Full,
+            finesse_retry: true,
+            lock_delay: 30,
+            lock_reset: LockReset::Move(MOVE_RESETS),
+            next_queue: 5,
+            theme: Theme::Guideline,
+            randomizer: Generator::Bag7,
+            handling: Handling::new(),
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use rand::{rngs::SmallRng, Rng};
+use tetrust::rng::Pcg32;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Shape {
     O,
     I,
@@ -12,7 +12,17 @@ pub enum Shape {
 }
 
 impl Shape {
-    pub fn rand(rng: &mut SmallRng) -> Self {
+    pub const ALL: [Shape; 7] = [
+        Shape::O,
+        Shape::I,
+        Shape::T,
+        Shape::S,
+        Shape::Z,
+        Shape::L,
+        Shape::J,
+    ];
+
+    pub fn rand(rng: &mut Pcg32) -> Self {
         match rng.gen_range(0, 7) {
             0 => Shape::O,
             1 => Shape::I,
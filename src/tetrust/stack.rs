@@ -1,16 +1,22 @@
 use grid::{Grid, Pos, Tile};
 use tetrust::color::Color;
 use tetrust::piece::Piece;
+use tetrust::theme::Theme;
 
-#[derive(Clone, Debug)]
+// hidden rows above the visible playfield that pieces spawn into
+pub const BUFFER_ROWS: usize = 20;
+pub const VISIBLE_ROWS: usize = 20;
+pub const STACK_ROWS: usize = BUFFER_ROWS + VISIBLE_ROWS;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stack {
-    pub bricks: [[Option<Color>; 10]; 20],
+    pub bricks: [[Option<Color>; 10]; STACK_ROWS],
 }
 
 impl Stack {
     pub fn new() -> Stack {
         Stack {
-            bricks: [[None; 10]; 20],
+            bricks: [[None; 10]; STACK_ROWS],
         }
     }
 
@@ -34,10 +40,16 @@ impl Stack {
         false
     }
 
+    pub fn occupied(&self, x: i32, y: i32) -> bool {
+        !(0..=9).contains(&x)
+            || !(0..STACK_ROWS as i32).contains(&y)
+            || self.bricks[y as usize][x as usize].is_some()
+    }
+
     pub fn remove_lines(&self) -> (Self, u32) {
         let mut bricks = self.bricks;
         let mut lines = 0;
-        let mut y = 19;
+        let mut y = STACK_ROWS - 1;
 
         loop {
             if bricks[y].iter().all(|x| x.is_some()) {
@@ -66,9 +78,13 @@ impl Stack {
         (Stack { bricks }, lines)
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.bricks.iter().flatten().all(|x| x.is_none())
+    }
+
     pub fn can_remove_lines(&self) -> bool {
         let bricks = self.bricks;
-        let mut y = 19;
+        let mut y = STACK_ROWS - 1;
 
         loop {
             if bricks[y].iter().all(|x| x.is_some()) {
@@ -110,25 +126,27 @@ impl Stack {
 }
 
 impl Grid {
-    pub fn draw_stack(&mut self, stack: &Stack, desolving_rows_timer: u32, x: u32, y: u32) {
+    pub fn draw_stack(
+        &mut self,
+        stack: &Stack,
+        desolving_rows_timer: u32,
+        x: u32,
+        y: u32,
+        theme: Theme,
+    ) {
         let flash = (desolving_rows_timer / 6) % 2 == 0;
 
-        for j in 0..20 {
-            if flash && stack.bricks[j].iter().all(|x| x.is_some()) {
+        for j in 0..VISIBLE_ROWS {
+            let row = &stack.bricks[BUFFER_ROWS + j];
+
+            if flash && row.iter().all(|x| x.is_some()) {
                 for i in 0..10 {
                     self[Pos(x + i, y + (j as u32))] = Tile::Space;
                 }
             } else {
                 for i in 0..10 {
-                    self[Pos(x + i, y + (j as u32))] = match stack.bricks[j as usize][i as usize] {
-                        Some(color) => match color {
-                            Color::Blue => Tile::BlueSolid,
-                            Color::BlueWhite => Tile::BlueWhite,
-                            Color::Cyan => Tile::CyanSolid,
-                            Color::CyanWhite => Tile::CyanWhite,
-                            Color::Grey => Tile::GreySolid,
-                            Color::GreyWhite => Tile::GreyWhite,
-                        },
+                    self[Pos(x + i, y + (j as u32))] = match row[i as usize] {
+                        Some(color) => theme.tile(color),
                         None => Tile::Space,
                     };
                 }
@@ -0,0 +1,64 @@
+use grid::Tile;
+use std::fmt::{Display, Formatter, Result};
+use tetrust::color::Color;
+use tetrust::shape::Shape;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Guideline,
+    Random,
+    GameBoy,
+}
+
+impl Theme {
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Guideline => Theme::Random,
+            Theme::Random => Theme::GameBoy,
+            Theme::GameBoy => Theme::Guideline,
+        }
+    }
+
+    // the random colour is drawn for every piece so that switching themes
+    // never changes the sequence of pieces dealt from a seed
+    pub fn color(self, shape: Shape, random: Color) -> Color {
+        match self {
+            Theme::Guideline | Theme::GameBoy => match shape {
+                Shape::I => Color::Cyan,
+                Shape::O => Color::Yellow,
+                Shape::T => Color::Purple,
+                Shape::S => Color::Green,
+                Shape::Z => Color::Red,
+                Shape::J => Color::Blue,
+                Shape::L => Color::Orange,
+            },
+            Theme::Random => random,
+        }
+    }
+
+    // the four shades of the game boy atlas only tell pieces apart with the
+    // white centred tiles
+    pub fn tile(self, color: Color) -> Tile {
+        match self {
+            Theme::Guideline | Theme::Random => color.tile(),
+            Theme::GameBoy => color.white_tile(),
+        }
+    }
+
+    pub fn atlas(self) -> &'static str {
+        match self {
+            Theme::Guideline | Theme::Random => "Tiles.png",
+            Theme::GameBoy => "TilesGameBoy.png",
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Theme::Guideline => write!(f, "Guideline"),
+            Theme::Random => write!(f, "Random"),
+            Theme::GameBoy => write!(f, "Game Boy"),
+        }
+    }
+}
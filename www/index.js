@@ -49,6 +49,10 @@ CANVAS.onmouseup = (event) => {
   };
 }
 
+window.onblur = () => {
+  wasm.on_blur(performance.now());
+};
+
 // TODO: gamepad support
 window.addEventListener("gamepadconnected", function(e) {
   console.log(
@@ -160,6 +164,8 @@ if (!GL.getProgramParameter(SHADER_PROGRAM, GL.LINK_STATUS)) {
 }
 
 const TILES_TEX = GL.createTexture();
+let ATLAS = wasm.atlas(performance.now());
+let ATLAS_LOADED = false;
 { // init tiles texture
 
   GL.bindTexture(GL.TEXTURE_2D, TILES_TEX);
@@ -178,26 +184,7 @@ const TILES_TEX = GL.createTexture();
 
   setTextureParameters(GL);
 
-  const image = new Image();
-
-  image.onload = () => {
-    GL.bindTexture(GL.TEXTURE_2D, TILES_TEX);
-
-    GL.texImage2D(
-      GL.TEXTURE_2D,
-      0,                // level
-      GL.RGBA,          // internalFormat
-      GL.RGBA,          // srcFormat
-      GL.UNSIGNED_BYTE, // srcType,
-      image
-    );
-
-    setTextureParameters(GL);
-
-    startGame();
-  };
-
-  image.src = "Tiles.png";
+  loadAtlas(ATLAS, startGame);
 }
 
 const TILE_MAP_TEX = GL.createTexture();
@@ -257,7 +244,72 @@ let SCALE = [1, 0, 0, 1];
 
 let t0 = performance.now();
 
+// keep the game in progress across page loads
+const SAVE_KEY = "tetrust-save";
+
+function saveGame() {
+  const bytes = wasm.save_state(performance.now());
+
+  if (bytes === undefined) {
+    localStorage.removeItem(SAVE_KEY);
+  } else {
+    localStorage.setItem(SAVE_KEY, btoa(String.fromCharCode(...bytes)));
+  }
+}
+
+window.addEventListener("pagehide", saveGame);
+document.addEventListener("visibilitychange", () => {
+  if (document.visibilityState === "hidden") {
+    saveGame();
+  }
+});
+
+// name given to high scores, asked for when a game places on the board
+const NAME_KEY = "tetrust-name";
+
+function askName(t) {
+  const name = prompt("New high score! Enter your name:", localStorage.getItem(NAME_KEY) || "PLAYER");
+
+  if (name !== null && name.trim() !== "") {
+    localStorage.setItem(NAME_KEY, name.trim());
+    wasm.rename_high_score(t, name.trim());
+  }
+}
+
 function startGame() {
+  {
+    const name = localStorage.getItem(NAME_KEY);
+
+    if (name !== null) {
+      wasm.set_name(performance.now(), name);
+    }
+  }
+
+  {
+    const saved = localStorage.getItem(SAVE_KEY);
+
+    if (saved !== null) {
+      try {
+        wasm.load_state(
+          performance.now(),
+          Uint8Array.from(atob(saved), (c) => c.charCodeAt(0))
+        );
+      } catch (error) {
+        console.log(error);
+        localStorage.removeItem(SAVE_KEY);
+      }
+    }
+  }
+
+  // links to a replay open straight into the player
+  if (location.hash.startsWith("#replay=")) {
+    try {
+      wasm.watch_replay(performance.now(), location.hash.slice("#replay=".length));
+    } catch (error) {
+      console.log(error);
+    }
+  }
+
   (function iterate(t) {
     if (t - t0 > 32) {
       // console.log(t - t0);
@@ -265,11 +317,33 @@ function startGame() {
 
     t0 = t;
     const scene = wasm.render(t, CANVAS_WIDTH, CANVAS_HEIGHT);
+    const atlas = wasm.atlas(t);
+
+    // reload the tile atlas when the theme changes
+    if (atlas !== ATLAS) {
+      ATLAS = atlas;
+      loadAtlas(atlas, () => { ATLAS_LOADED = true; });
+    }
+
+    for (const event of wasm.take_events(t)) {
+      if (event === "results") {
+        const code = wasm.replay_code(t);
+
+        if (code !== undefined) {
+          history.replaceState(null, "", "#replay=" + code);
+        }
+      } else if (event === "new_high_score") {
+        askName(t);
+      }
+      window.dispatchEvent(new CustomEvent("tetrust", { detail: event }));
+    }
 
     if (scene.changed ||
+        ATLAS_LOADED ||
         scene.grid_width !== GRID_WIDTH ||
         scene.grid_height !== GRID_HEIGHT) {
       try {
+        ATLAS_LOADED = false;
         GL.useProgram(SHADER_PROGRAM);
 
         // update stale srcToOrigin texture
@@ -398,6 +472,29 @@ function resize() {
   }
 }
 
+function loadAtlas(src, onload) {
+  const image = new Image();
+
+  image.onload = () => {
+    GL.bindTexture(GL.TEXTURE_2D, TILES_TEX);
+
+    GL.texImage2D(
+      GL.TEXTURE_2D,
+      0,                // level
+      GL.RGBA,          // internalFormat
+      GL.RGBA,          // srcFormat
+      GL.UNSIGNED_BYTE, // srcType,
+      image
+    );
+
+    setTextureParameters(GL);
+
+    onload();
+  };
+
+  image.src = src;
+}
+
 function setTextureParameters(gl) {
   gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE);
   gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE);